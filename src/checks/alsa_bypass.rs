@@ -0,0 +1,91 @@
+//! Check 9: ALSA Bypass
+//! Detects apps that open ALSA hardware directly and never reach the audio server.
+
+use super::sink_inputs::parse_sink_inputs;
+use crate::runner::{run_command, CommandRunner};
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "alsa_bypass";
+
+/// Apps that commonly open `hw:`/`plughw:` devices through PortAudio or raw ALSA.
+pub const DEFAULT_DIRECT_ALSA_APPS: &[&str] = &[
+    "audacity",
+    "reaper",
+    "renoise",
+    "bitwig-studio",
+    "mixxx",
+    "sunvox",
+];
+
+/// Check if a known direct-ALSA app is running without a sink-input.
+pub fn check_alsa_bypass(apps: &[String], runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    // Find which of the listed apps are running
+    let mut running: Vec<(String, Vec<u32>)> = Vec::new();
+
+    for app in apps {
+        let pgrep_output = run_command("pgrep", &["-x", app]);
+        let pids: Vec<u32> = pgrep_output
+            .stdout
+            .lines()
+            .filter_map(|l| l.trim().parse().ok())
+            .collect();
+
+        if !pids.is_empty() {
+            debug_info.push_str(&format!("pgrep -x {}: {:?}\n", app, pids));
+            running.push((app.clone(), pids));
+        }
+    }
+
+    if running.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "No direct-ALSA apps running").with_debug(debug_info);
+    }
+
+    let inputs_output = runner.run("pactl", &["list", "sink-inputs"]);
+    if !inputs_output.success {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "No ALSA bypass issues (cannot list active audio streams)",
+        )
+        .with_debug(debug_info);
+    }
+
+    let inputs = parse_sink_inputs(&inputs_output.stdout);
+
+    // An app has a stream if one of its PIDs or its binary name shows up
+    let bypassing: Vec<&str> = running
+        .iter()
+        .filter(|(app, pids)| {
+            !inputs.iter().any(|input| {
+                input.process_id.map(|p| pids.contains(&p)).unwrap_or(false)
+                    || input.process_binary == *app
+            })
+        })
+        .map(|(app, _)| app.as_str())
+        .collect();
+
+    debug_info.push_str(&format!("running without a stream: {:?}\n", bypassing));
+
+    if bypassing.is_empty() {
+        CheckResult::ok(
+            CHECK_NAME,
+            format!(
+                "{} direct-ALSA app(s) running and routed through the audio server",
+                running.len()
+            ),
+        )
+        .with_debug(debug_info)
+    } else {
+        CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "{} running with no audio stream (may be bypassing PipeWire via ALSA hw/plughw)",
+                bypassing.join(", ")
+            ),
+            "Select the 'pipewire' or 'default' ALSA device in the app's audio settings instead of a hw:/plughw: device",
+        )
+        .with_code("alsa.direct_bypass")
+        .with_debug(debug_info)
+    }
+}
@@ -0,0 +1,89 @@
+//! Check 24: ALSA Configuration Files
+//! Detects an `~/.asoundrc` or `/etc/asound.conf` that leaves the `default` ALSA PCM unresolvable.
+
+use std::path::PathBuf;
+
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "alsa_config";
+
+/// System-wide ALSA configuration read by every ALSA app.
+const SYSTEM_CONFIG: &str = "/etc/asound.conf";
+
+/// Check that the ALSA `default` PCM still resolves when custom config files exist.
+pub fn check_alsa_config(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let configs: Vec<PathBuf> = config_candidates()
+        .into_iter()
+        .filter(|p| p.is_file())
+        .collect();
+    debug_info.push_str(&format!(
+        "ALSA config files found: {}\n",
+        if configs.is_empty() {
+            "none".to_string()
+        } else {
+            configs
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    ));
+
+    if configs.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "No custom ALSA configuration").with_debug(debug_info);
+    }
+
+    let output = runner.run("aplay", &["-L"]);
+    debug_info.push_str(&format!("aplay -L:\n{}{}\n", output.stdout, output.stderr));
+
+    // A broken include in the config also reports "No such file", so only
+    // a failed spawn means aplay itself is missing
+    if output.stderr.starts_with("Failed to execute command") {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "ALSA configuration not checked (aplay not installed)",
+        )
+        .with_debug(debug_info);
+    }
+
+    // PCM names start in the first column; their descriptions are indented
+    let has_default = output.stdout.lines().any(|l| l.trim_end() == "default");
+    let config_errors = output.stderr.lines().any(|l| l.starts_with("ALSA lib"));
+
+    if output.success && has_default && !config_errors {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Custom ALSA configuration resolves the default device",
+        )
+        .with_debug(debug_info);
+    }
+
+    let files = configs
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" or ");
+    CheckResult::warning(
+        CHECK_NAME,
+        format!("The ALSA 'default' device doesn't resolve; check {}", files),
+        format!(
+            "Fix or temporarily rename {} (ALSA-only apps are silent until 'default' works)",
+            files
+        ),
+    )
+    .with_code("alsa_config.broken_default")
+    .with_debug(debug_info)
+}
+
+/// `~/.asoundrc` (when HOME is set) and `/etc/asound.conf`.
+fn config_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(home) = std::env::var_os("HOME") {
+        candidates.push(PathBuf::from(home).join(".asoundrc"));
+    }
+    candidates.push(PathBuf::from(SYSTEM_CONFIG));
+    candidates
+}
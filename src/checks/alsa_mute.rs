@@ -0,0 +1,110 @@
+//! Check 13: ALSA Mixer Mute
+//! Detects playback controls muted, or the PCM softvol turned down, underneath the audio server.
+
+use super::mute_state::parse_channel_percentages;
+use crate::runner::run_command;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "alsa_mute";
+
+/// Mixer controls that silence playback when switched off.
+const CONTROLS: &[&str] = &["Master", "Speaker", "Headphone"];
+
+/// Below this, the `PCM` control makes audio near-silent while the audio
+/// server still shows full volume.
+const PCM_LOW_VOLUME_PERCENT: u32 = 10;
+
+/// Check if a relevant ALSA playback control on card 0 is switched off.
+pub fn check_alsa_mute() -> CheckResult {
+    let mut debug_info = String::new();
+    let mut muted: Vec<&str> = Vec::new();
+    let mut found_any = false;
+
+    for control in CONTROLS {
+        let output = run_command("amixer", &["-c", "0", "sget", control]);
+        debug_info.push_str(&format!(
+            "amixer -c 0 sget {}:\n{}{}\n",
+            control, output.stdout, output.stderr
+        ));
+
+        if !output.success {
+            if output.stderr.contains("not found") || output.stderr.contains("No such file") {
+                return CheckResult::warning(
+                    CHECK_NAME,
+                    "Cannot check ALSA mixer (amixer not installed)",
+                    "Install alsa-utils package for full diagnostics",
+                )
+                .with_code("alsa.amixer_missing")
+                .with_debug(debug_info);
+            }
+            // The card simply doesn't have this control
+            continue;
+        }
+
+        found_any = true;
+        if parse_switch_off(&output.stdout) {
+            muted.push(control);
+        }
+    }
+
+    if !muted.is_empty() {
+        CheckResult::error(
+            CHECK_NAME,
+            format!("ALSA mixer control(s) switched off: {}", muted.join(", ")),
+            format!(
+                "Unmute with: amixer -c 0 sset {} unmute (or press M in alsamixer)",
+                muted[0]
+            ),
+        )
+        .with_code("alsa.control_muted")
+        .with_fix(&["amixer", "-c", "0", "sset", muted[0], "unmute"])
+        .with_debug(debug_info)
+    } else if let Some(volume) = low_pcm_volume(&mut debug_info) {
+        CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "ALSA PCM volume is very low ({}%), quiet even at full output volume",
+                volume
+            ),
+            "Raise it with: amixer -c 0 sset PCM 100% (or in alsamixer)",
+        )
+        .with_code("alsa.pcm_volume_low")
+        .with_fix(&["amixer", "-c", "0", "sset", "PCM", "100%"])
+        .with_debug(debug_info)
+    } else if found_any {
+        CheckResult::ok(CHECK_NAME, "ALSA mixer playback controls are on").with_debug(debug_info)
+    } else {
+        CheckResult::ok(CHECK_NAME, "No ALSA mixer playback controls to check")
+            .with_debug(debug_info)
+    }
+}
+
+/// The loudest channel of the `PCM` control on card 0, if the card has one
+/// and it is below `PCM_LOW_VOLUME_PERCENT`.
+fn low_pcm_volume(debug_info: &mut String) -> Option<u32> {
+    let output = run_command("amixer", &["-c", "0", "sget", "PCM"]);
+    debug_info.push_str(&format!(
+        "amixer -c 0 sget PCM:\n{}{}\n",
+        output.stdout, output.stderr
+    ));
+    if !output.success {
+        return None;
+    }
+
+    output
+        .stdout
+        .lines()
+        .filter(|l| l.contains("Playback") && l.contains(':'))
+        .flat_map(parse_channel_percentages)
+        .max()
+        .filter(|&v| v < PCM_LOW_VOLUME_PERCENT)
+}
+
+/// Returns true if any playback channel line ends in `[off]`, e.g.
+/// `Front Left: Playback 0 [0%] [-64.00dB] [off]`.
+fn parse_switch_off(output: &str) -> bool {
+    output
+        .lines()
+        .filter(|l| l.contains("Playback") && l.contains(':'))
+        .any(|l| l.contains("[off]"))
+}
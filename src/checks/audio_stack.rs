@@ -1,17 +1,20 @@
 //! Check 1: Audio Stack Status
 //! Detects whether PipeWire, WirePlumber, or PulseAudio is running.
 
-use crate::runner::run_command;
+use std::fs;
+
+use super::card_parse::parse_cards;
+use crate::runner::{run_command_retry, CommandRunner};
 use crate::types::CheckResult;
 
-const CHECK_NAME: &str = "audio_stack";
+pub(super) const CHECK_NAME: &str = "audio_stack";
 
 /// Check the status of the audio stack (PipeWire, WirePlumber, PulseAudio).
-pub fn check_audio_stack() -> CheckResult {
+pub fn check_audio_stack(runner: &dyn CommandRunner) -> CheckResult {
     let mut debug_info = String::new();
 
     // Check PipeWire
-    let pipewire_output = run_command("systemctl", &["--user", "is-active", "pipewire"]);
+    let pipewire_output = runner.run("systemctl", &["--user", "is-active", "pipewire"]);
     debug_info.push_str(&format!(
         "systemctl --user is-active pipewire:\n{}\n",
         pipewire_output.stdout.trim()
@@ -19,15 +22,34 @@ pub fn check_audio_stack() -> CheckResult {
     let pipewire_running = pipewire_output.stdout.trim() == "active";
 
     // Check WirePlumber
-    let wireplumber_output = run_command("systemctl", &["--user", "is-active", "wireplumber"]);
+    let wireplumber_output = runner.run("systemctl", &["--user", "is-active", "wireplumber"]);
     debug_info.push_str(&format!(
         "systemctl --user is-active wireplumber:\n{}\n",
         wireplumber_output.stdout.trim()
     ));
     let wireplumber_running = wireplumber_output.stdout.trim() == "active";
 
-    // Check PulseAudio via pactl
-    let pactl_output = run_command("pactl", &["info"]);
+    // Check for a real PulseAudio daemon (service or socket activation)
+    let pulseaudio_output = runner.run(
+        "systemctl",
+        &[
+            "--user",
+            "is-active",
+            "pulseaudio.service",
+            "pulseaudio.socket",
+        ],
+    );
+    debug_info.push_str(&format!(
+        "systemctl --user is-active pulseaudio.service pulseaudio.socket:\n{}\n",
+        pulseaudio_output.stdout.trim()
+    ));
+    let pulseaudio_unit_active = pulseaudio_output
+        .stdout
+        .lines()
+        .any(|l| l.trim() == "active");
+
+    // Check PulseAudio via pactl; pipewire-pulse may still be starting after login
+    let pactl_output = run_command_retry(runner, "pactl", &["info"]);
     debug_info.push_str(&format!(
         "pactl info (first 500 chars):\n{}\n",
         pactl_output.stdout.chars().take(500).collect::<String>()
@@ -44,22 +66,37 @@ pub fn check_audio_stack() -> CheckResult {
 
     let is_pipewire_pulse = server_name.to_lowercase().contains("pipewire");
 
+    // The real daemon answers pactl as "pulseaudio"; pipewire-pulse names PipeWire
+    let real_pulseaudio_serving = pactl_works && !is_pipewire_pulse;
+
     // Analyze the results
-    if pipewire_running && wireplumber_running {
-        CheckResult::ok(CHECK_NAME, "PipeWire and WirePlumber are running").with_debug(debug_info)
-    } else if pipewire_running && !wireplumber_running {
-        CheckResult::warning(
+    if pipewire_running && (pulseaudio_unit_active || real_pulseaudio_serving) {
+        CheckResult::error(
             CHECK_NAME,
-            "PipeWire is running but WirePlumber is not",
-            "Start WirePlumber: systemctl --user start wireplumber",
+            format!(
+                "PipeWire and a real PulseAudio daemon are both running (pactl server: {})",
+                if server_name.is_empty() { "unknown" } else { server_name }
+            ),
+            "Disable PulseAudio: systemctl --user mask --now pulseaudio.service pulseaudio.socket && systemctl --user restart pipewire-pulse",
         )
+        .with_code("stack.pulseaudio_conflict")
         .with_debug(debug_info)
+    } else if pipewire_running && wireplumber_running {
+        if let Some(result) = check_device_nodes(runner, &mut debug_info) {
+            return result.with_debug(debug_info);
+        }
+        CheckResult::ok(CHECK_NAME, "PipeWire and WirePlumber are running").with_debug(debug_info)
+    } else if pipewire_running && !wireplumber_running {
+        check_session_manager(runner, debug_info)
     } else if !pipewire_running && pactl_works && !is_pipewire_pulse {
         // PulseAudio fallback mode
         CheckResult::ok(CHECK_NAME, "PulseAudio is running (legacy mode)").with_debug(debug_info)
     } else if !pipewire_running && pactl_works && is_pipewire_pulse {
         // PipeWire-pulse is responding but systemd says pipewire isn't active
         // This can happen with socket activation
+        if let Some(result) = check_device_nodes(runner, &mut debug_info) {
+            return result.with_debug(debug_info);
+        }
         CheckResult::ok(CHECK_NAME, "PipeWire is running (socket-activated)").with_debug(debug_info)
     } else if !pactl_works {
         CheckResult::error(
@@ -67,6 +104,15 @@ pub fn check_audio_stack() -> CheckResult {
             "No audio server detected",
             "Start PipeWire: systemctl --user start pipewire pipewire-pulse wireplumber",
         )
+        .with_code("stack.no_server")
+        .with_fix(&[
+            "systemctl",
+            "--user",
+            "start",
+            "pipewire",
+            "pipewire-pulse",
+            "wireplumber",
+        ])
         .with_debug(debug_info)
     } else {
         CheckResult::warning(
@@ -74,6 +120,117 @@ pub fn check_audio_stack() -> CheckResult {
             "Audio stack status is unclear",
             "Check your audio server manually: systemctl --user status pipewire",
         )
+        .with_code("stack.unclear")
         .with_debug(debug_info)
     }
 }
+
+/// pipewire-pulse can keep answering after the session manager died, leaving
+/// a server with no device nodes. Returns an error when the server lists
+/// neither real sinks nor cards although the kernel has sound cards; cards
+/// that are merely set to the off profile are the card_output_profile check's
+/// finding.
+fn check_device_nodes(runner: &dyn CommandRunner, debug_info: &mut String) -> Option<CheckResult> {
+    let sinks_output = runner.run("pactl", &["list", "short", "sinks"]);
+    if !sinks_output.success {
+        return None;
+    }
+
+    // The null sink is the server's placeholder when it has no devices
+    let sink_count = sinks_output
+        .stdout
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.contains("auto_null"))
+        .count();
+
+    let card_count = fs::read_to_string("/proc/asound/cards")
+        .map(|cards| {
+            cards
+                .lines()
+                .filter(|l| l.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+                .count()
+        })
+        .unwrap_or(0);
+
+    debug_info.push_str(&format!(
+        "server sinks: {}, ALSA cards: {}\n",
+        sink_count, card_count
+    ));
+
+    if sink_count > 0 || card_count == 0 {
+        return None;
+    }
+
+    let cards_output = runner.run("pactl", &["list", "cards"]);
+    if !cards_output.success {
+        return None;
+    }
+    let server_cards = parse_cards(&cards_output.stdout).len();
+    debug_info.push_str(&format!("server cards: {}\n", server_cards));
+    if server_cards > 0 {
+        return None;
+    }
+
+    Some(
+        CheckResult::error(
+            CHECK_NAME,
+            format!(
+                "PipeWire answers but exposes no sound cards, although the kernel has {} sound card(s) (session manager likely crashed)",
+                card_count
+            ),
+            "Restart the session manager: systemctl --user restart wireplumber",
+        )
+        .with_code("stack.no_device_nodes")
+        .with_fix(&["systemctl", "--user", "restart", "wireplumber"]),
+    )
+}
+
+/// PipeWire is up but WirePlumber isn't active: tell apart a stopped session
+/// manager from one that was never installed, and accept pipewire-media-session.
+fn check_session_manager(runner: &dyn CommandRunner, mut debug_info: String) -> CheckResult {
+    let media_session_output = runner.run(
+        "systemctl",
+        &["--user", "is-active", "pipewire-media-session"],
+    );
+    debug_info.push_str(&format!(
+        "systemctl --user is-active pipewire-media-session:\n{}\n",
+        media_session_output.stdout.trim()
+    ));
+    if media_session_output.stdout.trim() == "active" {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "PipeWire and pipewire-media-session are running",
+        )
+        .with_debug(debug_info);
+    }
+
+    // `systemctl cat` fails when no unit file exists at all
+    let unit_output = runner.run("systemctl", &["--user", "cat", "wireplumber"]);
+    debug_info.push_str(&format!(
+        "systemctl --user cat wireplumber: {}\n",
+        if unit_output.success {
+            "found"
+        } else {
+            unit_output.stderr.trim()
+        }
+    ));
+
+    if !unit_output.success {
+        return CheckResult::error(
+            CHECK_NAME,
+            "PipeWire is running but no session manager is installed (no audio devices will appear)",
+            "Install WirePlumber (package 'wireplumber'), then log out and back in",
+        )
+        .with_code("stack.no_session_manager")
+        .with_debug(debug_info);
+    }
+
+    CheckResult::warning(
+        CHECK_NAME,
+        "PipeWire is running but WirePlumber is not",
+        "Start WirePlumber: systemctl --user start wireplumber",
+    )
+    .with_code("stack.wireplumber_inactive")
+    .with_fix(&["systemctl", "--user", "start", "wireplumber"])
+    .with_debug(debug_info)
+}
@@ -1,21 +1,30 @@
 //! Check 6: Bluetooth Profile Trap
 //! Detects if Bluetooth is using HSP/HFP instead of A2DP.
 
-use crate::runner::run_command;
+use super::card_parse::{parse_cards, Card};
+use super::parsing::{blocks, property};
+use crate::runner::CommandRunner;
 use crate::types::CheckResult;
 
-const CHECK_NAME: &str = "bluetooth_profile";
+pub(super) const CHECK_NAME: &str = "bluetooth_profile";
+
+/// Codecs that sound better than plain SBC, best first, as named in
+/// PipeWire's per-codec profiles (`a2dp-sink-ldac`, ...).
+const BETTER_CODECS: &[&str] = &["ldac", "aptx_hd", "aptx", "aac", "sbc_xq"];
+
+/// Card and sink properties that name the active codec: PipeWire's, then PulseAudio's.
+const CODEC_PROPERTIES: &[&str] = &["api.bluez5.codec", "bluez.codec", "bluetooth.codec"];
 
 /// Check if Bluetooth audio is in the wrong profile mode.
-pub fn check_bluetooth_profile() -> CheckResult {
+pub fn check_bluetooth_profile(runner: &dyn CommandRunner) -> CheckResult {
     let mut debug_info = String::new();
 
     // Get default sink to check if it's Bluetooth
-    let default_sink_output = run_command("pactl", &["get-default-sink"]);
+    let default_sink_output = runner.run("pactl", &["get-default-sink"]);
     let default_sink = default_sink_output.stdout.trim();
 
     // List cards to find Bluetooth devices
-    let cards_output = run_command("pactl", &["list", "cards"]);
+    let cards_output = runner.run("pactl", &["list", "cards"]);
     debug_info.push_str(&format!(
         "pactl list cards (bluetooth info):\n{}\n",
         cards_output
@@ -41,26 +50,62 @@ pub fn check_bluetooth_profile() -> CheckResult {
     }
 
     // Parse Bluetooth cards
-    let bt_cards = parse_bluetooth_cards(&cards_output.stdout);
+    let bt_cards: Vec<_> = parse_cards(&cards_output.stdout)
+        .into_iter()
+        .filter(|c| c.is_bluetooth())
+        .collect();
 
     if bt_cards.is_empty() {
         return CheckResult::ok(CHECK_NAME, "No Bluetooth audio devices connected")
             .with_debug(debug_info);
     }
 
+    // The active codec is often only on the sink, not the card
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
+
     // Check each Bluetooth card for HSP/HFP profile
     let mut issues: Vec<String> = Vec::new();
     let mut has_active_bt = false;
+    // Codec of the card behind the default sink, with a better one it offers
+    let mut active_codec: Option<(&Card, String, Option<&str>)> = None;
+    // Connected cards with no audio profile at all, with an A2DP profile to switch to
+    let mut profile_off: Vec<(&Card, Option<&str>)> = Vec::new();
 
     for card in &bt_cards {
-        // Check if this card is the active sink
-        let is_active = default_sink.contains(&card.name)
+        // Card and sink names differ (bluez_card.X vs bluez_output.X.1), but
+        // both carry the device's address
+        let card_address = bluetooth_address(&card.name);
+        let is_active = (card_address.is_some() && card_address == bluetooth_address(default_sink))
             || card.sinks.iter().any(|s| default_sink.contains(s.as_str()));
 
         if is_active {
             has_active_bt = true;
         }
 
+        let codec = card_codec(card, &sinks_output.stdout);
+        if let Some(ref codec) = codec {
+            debug_info.push_str(&format!("{}: codec {}\n", card.name, codec));
+        }
+        if let Some(codec) = codec.filter(|_| is_active) {
+            let better = if codec == "sbc" {
+                better_codec(card)
+            } else {
+                None
+            };
+            active_codec = Some((card, codec, better));
+        }
+
+        if card.active_profile.is_empty() || card.active_profile == "off" {
+            let a2dp = card
+                .profiles
+                .iter()
+                .filter(|p| p.available && p.name.to_lowercase().contains("a2dp"))
+                .max_by_key(|p| p.priority)
+                .map(|p| p.name.as_str());
+            profile_off.push((card, a2dp));
+            continue;
+        }
+
         // Check for problematic profiles
         let profile_lower = card.active_profile.to_lowercase();
         let is_hsp_hfp = profile_lower.contains("hsp")
@@ -68,20 +113,22 @@ pub fn check_bluetooth_profile() -> CheckResult {
             || profile_lower.contains("headset-head-unit");
 
         let has_a2dp = card
-            .available_profiles
+            .profiles
             .iter()
-            .any(|p| p.to_lowercase().contains("a2dp"));
+            .any(|p| p.name.to_lowercase().contains("a2dp"));
 
         if is_hsp_hfp {
             if has_a2dp {
                 issues.push(format!(
                     "'{}' is in call/headset mode ({}), A2DP available",
-                    card.description, card.active_profile
+                    card.description(),
+                    card.active_profile
                 ));
             } else {
                 issues.push(format!(
                     "'{}' is in call/headset mode ({}), A2DP not available",
-                    card.description, card.active_profile
+                    card.description(),
+                    card.active_profile
                 ));
             }
         }
@@ -96,6 +143,7 @@ pub fn check_bluetooth_profile() -> CheckResult {
                 format!("Bluetooth headset in call mode: {}", issues.join("; ")),
                 "Switch Bluetooth profile to A2DP (high-quality audio) in sound settings",
             )
+            .with_code("bluetooth.headset_mode")
             .with_debug(debug_info);
         } else if has_active_bt {
             return CheckResult::warning(
@@ -103,6 +151,7 @@ pub fn check_bluetooth_profile() -> CheckResult {
                 format!("Bluetooth in low-quality mode: {}", issues.join("; ")),
                 "A2DP profile may not be available. Check if device supports it.",
             )
+            .with_code("bluetooth.low_quality")
             .with_debug(debug_info);
         } else {
             // Bluetooth is connected but not the active output
@@ -114,133 +163,257 @@ pub fn check_bluetooth_profile() -> CheckResult {
                 ),
                 "If using Bluetooth, switch profile to A2DP for better quality",
             )
+            .with_code("bluetooth.inactive_headset_mode")
             .with_debug(debug_info);
         }
     }
 
-    CheckResult::ok(
-        CHECK_NAME,
-        if has_active_bt {
-            "Bluetooth audio profile is optimal (A2DP)"
-        } else if !bt_cards.is_empty() {
-            "Bluetooth device connected with correct profile"
-        } else {
-            "No Bluetooth audio issues"
-        },
-    )
-    .with_debug(debug_info)
+    // With no profile there is no sink, so the device can never be the output
+    if let Some(&(card, a2dp)) = profile_off.first() {
+        let result = CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "Bluetooth device '{}' is connected but has no active audio profile",
+                card.description()
+            ),
+            match a2dp {
+                Some(profile) => format!(
+                    "Select the A2DP profile in sound settings, or: pactl set-card-profile {} {}",
+                    card.name, profile
+                ),
+                None => {
+                    "Select an A2DP (high fidelity playback) profile in sound settings".to_string()
+                }
+            },
+        )
+        .with_code("bluetooth.profile_off")
+        .with_debug(debug_info);
+
+        return match a2dp {
+            Some(profile) => result.with_fix(&["pactl", "set-card-profile", &card.name, profile]),
+            None => result,
+        };
+    }
+
+    // Plain SBC is the usual cause of "muffled" Bluetooth audio
+    if let Some((card, _, Some(better))) = active_codec {
+        let profile = format!("a2dp-sink-{}", better);
+        return CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "Bluetooth device '{}' uses the SBC codec, but {} is available",
+                card.description(),
+                better.to_uppercase()
+            ),
+            format!(
+                "Switch the codec in sound settings, or: pactl set-card-profile {} {}",
+                card.name, profile
+            ),
+        )
+        .with_code("bluetooth.sbc_codec")
+        .with_fix(&["pactl", "set-card-profile", &card.name, &profile])
+        .with_debug(debug_info);
+    }
+
+    let message = match active_codec {
+        Some((_, codec, _)) => format!(
+            "Bluetooth audio profile is optimal (A2DP, {} codec)",
+            codec.to_uppercase()
+        ),
+        None if has_active_bt => "Bluetooth audio profile is optimal (A2DP)".to_string(),
+        None if !bt_cards.is_empty() => {
+            "Bluetooth device connected with correct profile".to_string()
+        }
+        None => "No Bluetooth audio issues".to_string(),
+    };
+    CheckResult::ok(CHECK_NAME, message).with_debug(debug_info)
 }
 
-struct BluetoothCard {
-    name: String,
-    description: String,
-    active_profile: String,
-    available_profiles: Vec<String>,
-    sinks: Vec<String>,
+/// The A2DP codec `card` is using, lowercase (e.g. `ldac`): from a codec
+/// property on the card or its sink in `sinks_output`, else from a per-codec
+/// profile name like `a2dp-sink-aac`. `None` when not in A2DP or not reported.
+fn card_codec(card: &Card, sinks_output: &str) -> Option<String> {
+    let from_card = CODEC_PROPERTIES.iter().find_map(|key| card.property(key));
+
+    let address = bluetooth_address(&card.name);
+    let from_sink = || {
+        blocks(sinks_output)
+            .into_iter()
+            .filter(|block| address.is_some() && bluetooth_address(block.name) == address)
+            .find_map(|block| {
+                block
+                    .text
+                    .lines()
+                    .find_map(|l| CODEC_PROPERTIES.iter().find_map(|key| property(l, key)))
+            })
+            .map(str::to_string)
+    };
+
+    let profile = card.active_profile.to_lowercase();
+    let from_profile = || {
+        profile
+            .strip_prefix("a2dp-sink-")
+            .or_else(|| profile.strip_prefix("a2dp_sink_"))
+            .map(str::to_string)
+    };
+
+    if !profile.contains("a2dp") {
+        return None;
+    }
+    from_card
+        .map(str::to_string)
+        .or_else(from_sink)
+        .or_else(from_profile)
+        .map(|codec| codec.to_lowercase())
 }
 
-fn parse_bluetooth_cards(output: &str) -> Vec<BluetoothCard> {
-    let mut cards = Vec::new();
-    let mut current_name = String::new();
-    let mut current_desc = String::new();
-    let mut current_profile = String::new();
-    let mut current_profiles: Vec<String> = Vec::new();
-    let mut current_sinks: Vec<String> = Vec::new();
-    let mut in_profiles_section = false;
-    let mut in_sinks_section = false;
-    let mut is_bluetooth = false;
-
-    for line in output.lines() {
-        let trimmed = line.trim();
-
-        // New card boundary
-        if trimmed.starts_with("Name:") {
-            // Save previous card if it's Bluetooth
-            if is_bluetooth && !current_name.is_empty() {
-                cards.push(BluetoothCard {
-                    name: current_name.clone(),
-                    description: current_desc.clone(),
-                    active_profile: current_profile.clone(),
-                    available_profiles: current_profiles.clone(),
-                    sinks: current_sinks.clone(),
-                });
-            }
+/// The best codec in `BETTER_CODECS` that `card` offers as an available profile.
+fn better_codec(card: &Card) -> Option<&'static str> {
+    BETTER_CODECS.iter().copied().find(|codec| {
+        card.profiles
+            .iter()
+            .any(|p| p.available && p.name.eq_ignore_ascii_case(&format!("a2dp-sink-{}", codec)))
+    })
+}
 
-            // Reset
-            current_name = trimmed
-                .strip_prefix("Name:")
-                .unwrap_or("")
-                .trim()
-                .to_string();
-            is_bluetooth = current_name.contains("bluez") || current_name.contains("bluetooth");
-            current_desc.clear();
-            current_profile.clear();
-            current_profiles.clear();
-            current_sinks.clear();
-            in_profiles_section = false;
-            in_sinks_section = false;
-        }
+/// The device address in a BlueZ card or sink name, e.g. `AA_BB_CC_DD_EE_FF`
+/// in `bluez_output.AA_BB_CC_DD_EE_FF.1`.
+fn bluetooth_address(name: &str) -> Option<&str> {
+    let address = name.split('.').nth(1)?;
+    let is_address = address
+        .split('_')
+        .all(|octet| octet.len() == 2 && octet.chars().all(|c| c.is_ascii_hexdigit()));
 
-        if !is_bluetooth {
-            continue;
-        }
+    is_address.then_some(address)
+}
 
-        if trimmed.starts_with("device.description = ") {
-            current_desc = trimmed
-                .strip_prefix("device.description = ")
-                .unwrap_or("")
-                .trim_matches('"')
-                .to_string();
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::fixtures;
+    use crate::runner::MockRunner;
+    use crate::types::CheckStatus;
 
-        if trimmed.starts_with("Active Profile:") {
-            current_profile = trimmed
-                .strip_prefix("Active Profile:")
-                .unwrap_or("")
-                .trim()
-                .to_string();
-        }
+    const HEADSET_SINK: &str = "bluez_output.AA_BB_CC_DD_EE_FF.1";
 
-        if trimmed.starts_with("Profiles:") {
-            in_profiles_section = true;
-            in_sinks_section = false;
-            continue;
-        }
+    /// Only the Bluetooth headset card, without its `Sinks:` section.
+    fn headset_card() -> String {
+        let card = fixtures::LIST_CARDS.split("\n\n").nth(2).unwrap();
+        card[..card.find("\tSinks:").unwrap()].to_string()
+    }
 
-        if trimmed.starts_with("Sinks:") {
-            in_sinks_section = true;
-            in_profiles_section = false;
-            continue;
-        }
+    fn pactl(default_sink: &str, cards: &str) -> MockRunner {
+        MockRunner::new()
+            .with("pactl get-default-sink", default_sink)
+            .with("pactl list cards", cards)
+            .with("pactl list sinks", "")
+    }
 
-        if in_profiles_section {
-            // Profile lines look like: "a2dp-sink: A2DP Sink (sinks: 1, sources: 0, priority: 40, available: yes)"
-            if let Some(colon_pos) = trimmed.find(':') {
-                let profile_name = trimmed[..colon_pos].trim();
-                if !profile_name.is_empty() && !profile_name.starts_with("Part of") {
-                    current_profiles.push(profile_name.to_string());
-                }
-            }
-        }
+    #[test]
+    fn default_sink_matches_its_card_by_address() {
+        let cards = headset_card().replace(
+            "Active Profile: a2dp-sink-sbc",
+            "Active Profile: headset-head-unit",
+        );
+        let result = check_bluetooth_profile(&pactl(HEADSET_SINK, &cards));
 
-        if in_sinks_section {
-            // Sink lines contain sink names
-            if trimmed.contains("bluez") || trimmed.starts_with("#") {
-                current_sinks.push(trimmed.to_string());
-            }
-        }
+        assert_eq!(result.code.as_deref(), Some("bluetooth.headset_mode"));
+    }
+
+    #[test]
+    fn other_devices_sink_is_not_active() {
+        let cards = headset_card().replace(
+            "Active Profile: a2dp-sink-sbc",
+            "Active Profile: headset-head-unit",
+        );
+        let result = check_bluetooth_profile(&pactl("bluez_output.11_22_33_44_55_66.1", &cards));
+
+        assert_eq!(result.status, CheckStatus::Warning);
+        assert_eq!(
+            result.code.as_deref(),
+            Some("bluetooth.inactive_headset_mode")
+        );
     }
 
-    // Don't forget the last card
-    if is_bluetooth && !current_name.is_empty() {
-        cards.push(BluetoothCard {
-            name: current_name,
-            description: current_desc,
-            active_profile: current_profile,
-            available_profiles: current_profiles,
-            sinks: current_sinks,
-        });
+    #[test]
+    fn extracts_the_device_address() {
+        assert_eq!(
+            bluetooth_address("bluez_card.AA_BB_CC_DD_EE_FF"),
+            Some("AA_BB_CC_DD_EE_FF")
+        );
+        assert_eq!(bluetooth_address(HEADSET_SINK), Some("AA_BB_CC_DD_EE_FF"));
+        assert_eq!(bluetooth_address(fixtures::DEFAULT_SINK.trim()), None);
     }
 
-    cards
+    #[test]
+    fn connected_device_without_a_profile_is_a_warning() {
+        let cards = headset_card().replace("Active Profile: a2dp-sink-sbc", "Active Profile: off");
+        let result = check_bluetooth_profile(&pactl(fixtures::DEFAULT_SINK, &cards));
+
+        assert_eq!(result.status, CheckStatus::Warning);
+        assert_eq!(result.code.as_deref(), Some("bluetooth.profile_off"));
+        // The highest-priority available A2DP profile; LDAC is unavailable
+        assert_eq!(
+            result.fix_command.as_ref().and_then(|cmd| cmd.last()),
+            Some(&"a2dp-sink-aac".to_string())
+        );
+    }
+
+    #[test]
+    fn sbc_with_a_better_codec_available_is_a_warning() {
+        let result = check_bluetooth_profile(&pactl(HEADSET_SINK, &headset_card()));
+
+        assert_eq!(result.code.as_deref(), Some("bluetooth.sbc_codec"));
+        assert!(result.message.contains("but AAC is available"));
+        assert_eq!(
+            result.fix_command.as_ref().and_then(|cmd| cmd.last()),
+            Some(&"a2dp-sink-aac".to_string())
+        );
+    }
+
+    #[test]
+    fn better_codec_is_reported_as_optimal() {
+        let cards = headset_card().replace(
+            "Active Profile: a2dp-sink-sbc",
+            "Active Profile: a2dp-sink-aac",
+        );
+        let result = check_bluetooth_profile(&pactl(HEADSET_SINK, &cards));
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert_eq!(
+            result.message,
+            "Bluetooth audio profile is optimal (A2DP, AAC codec)"
+        );
+    }
+
+    #[test]
+    fn codec_comes_from_properties_before_the_profile_name() {
+        let card = &parse_cards(&headset_card())[0];
+        assert_eq!(card_codec(card, ""), Some("sbc".to_string()));
+        assert_eq!(better_codec(card), Some("aac"));
+
+        let sinks = format!(
+            "Sink #70\n\tName: {}\n\tProperties:\n\t\tapi.bluez5.codec = \"LDAC\"\n",
+            HEADSET_SINK
+        );
+        assert_eq!(card_codec(card, &sinks), Some("ldac".to_string()));
+
+        let cards = headset_card().replace(
+            "\tProperties:\n",
+            "\tProperties:\n\t\tbluez.codec = \"aptx\"\n",
+        );
+        let card = &parse_cards(&cards)[0];
+        assert_eq!(card_codec(card, &sinks), Some("aptx".to_string()));
+    }
+
+    #[test]
+    fn headset_mode_has_no_codec() {
+        let cards = headset_card().replace(
+            "Active Profile: a2dp-sink-sbc",
+            "Active Profile: headset-head-unit",
+        );
+        let card = &parse_cards(&cards)[0];
+
+        assert_eq!(card_codec(card, ""), None);
+    }
 }
@@ -0,0 +1,203 @@
+//! Check 26: Card Coverage
+//! Detects ALSA sound cards that never made it into the PulseAudio/PipeWire graph.
+
+use super::card_parse::{parse_cards, Card};
+use super::parsing::{blocks, property};
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "card_coverage";
+
+/// A playback card from `aplay -l`.
+#[derive(Debug, PartialEq)]
+struct AlsaCard {
+    index: String,
+    name: String,
+}
+
+/// Check that every ALSA playback card has a sink in the audio server.
+pub fn check_card_coverage(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let aplay_output = runner.run("aplay", &["-l"]);
+    if aplay_output.stderr.starts_with("Failed to execute command") {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Card coverage not checked (aplay not installed)",
+        );
+    }
+
+    let alsa_cards = parse_aplay_cards(&aplay_output.stdout);
+    debug_info.push_str(&format!(
+        "ALSA playback cards: {}\n",
+        alsa_cards
+            .iter()
+            .map(|c| format!("{} ({})", c.index, c.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+
+    if alsa_cards.is_empty() {
+        // No cards at all is the audio_devices check's finding
+        return CheckResult::ok(CHECK_NAME, "Card coverage not checked (no ALSA cards)")
+            .with_debug(debug_info);
+    }
+
+    let cards_output = runner.run("pactl", &["list", "cards"]);
+    if !cards_output.success {
+        return CheckResult::ok(CHECK_NAME, "Card coverage not checked (cannot list cards)")
+            .with_debug(debug_info);
+    }
+
+    let server_cards = parse_cards(&cards_output.stdout);
+    for card in &server_cards {
+        debug_info.push_str(&format!(
+            "server card {}: alsa.card={}, alsa.card_name={}, profile '{}'\n",
+            card.name,
+            card.property("alsa.card").unwrap_or("-"),
+            card.property("alsa.card_name").unwrap_or("-"),
+            card.active_profile
+        ));
+    }
+
+    // With no cards at all the session manager is down, which the
+    // audio_stack check reports
+    if server_cards.is_empty() {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Card coverage not checked (the audio server lists no cards)",
+        )
+        .with_debug(debug_info);
+    }
+
+    // Sinks name their ALSA card in the `alsa.card` property
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
+    if !sinks_output.success {
+        return CheckResult::ok(CHECK_NAME, "Card coverage not checked (cannot list sinks)")
+            .with_debug(debug_info);
+    }
+    let sink_cards: Vec<&str> = blocks(&sinks_output.stdout)
+        .iter()
+        .filter_map(|block| block.text.lines().find_map(|l| property(l, "alsa.card")))
+        .collect();
+    debug_info.push_str(&format!(
+        "ALSA cards with a sink: {}\n",
+        sink_cards.join(", ")
+    ));
+
+    let uncovered: Vec<&AlsaCard> = alsa_cards
+        .iter()
+        .filter(|alsa| !sink_cards.contains(&alsa.index.as_str()))
+        .filter(|alsa| match find_server_card(&server_cards, alsa) {
+            None => true,
+            // A profile without outputs is the card_output_profile check's finding
+            Some(card) => active_profile_has_sinks(card),
+        })
+        .collect();
+
+    if uncovered.is_empty() {
+        return CheckResult::ok(
+            CHECK_NAME,
+            format!(
+                "All {} ALSA card(s) are known to the audio server",
+                alsa_cards.len()
+            ),
+        )
+        .with_debug(debug_info);
+    }
+
+    CheckResult::warning(
+        CHECK_NAME,
+        format!(
+            "{} of {} ALSA card(s) have no output in the audio server: {}",
+            uncovered.len(),
+            alsa_cards.len(),
+            uncovered
+                .iter()
+                .map(|c| format!("card {} ({})", c.index, c.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        "The session manager didn't pick these cards up. Check the card profiles with 'pactl list cards', or restart it: systemctl --user restart wireplumber",
+    )
+    .with_code("card.no_sink")
+    .with_debug(debug_info)
+}
+
+/// The server card for `alsa`, matched by ALSA card index, else by card name.
+fn find_server_card<'a>(cards: &'a [Card], alsa: &AlsaCard) -> Option<&'a Card> {
+    cards
+        .iter()
+        .find(|c| c.property("alsa.card") == Some(alsa.index.as_str()))
+        .or_else(|| {
+            cards
+                .iter()
+                .find(|c| c.property("alsa.card_name") == Some(alsa.name.as_str()))
+        })
+}
+
+/// Whether the card's active profile claims to provide a sink.
+fn active_profile_has_sinks(card: &Card) -> bool {
+    card.profiles
+        .iter()
+        .any(|p| p.name == card.active_profile && p.sinks > 0)
+}
+
+/// Unique cards from `aplay -l` lines like
+/// `card 0: PCH [HDA Intel PCH], device 0: ALC257 Analog [ALC257 Analog]`.
+fn parse_aplay_cards(output: &str) -> Vec<AlsaCard> {
+    let mut cards: Vec<AlsaCard> = Vec::new();
+
+    for rest in output.lines().filter_map(|l| l.strip_prefix("card ")) {
+        let index = rest.split(':').next().unwrap_or("").trim();
+        let name = rest
+            .split_once('[')
+            .and_then(|(_, after)| after.split_once(']'))
+            .map(|(name, _)| name)
+            .unwrap_or("");
+
+        let card = AlsaCard {
+            index: index.to_string(),
+            name: name.to_string(),
+        };
+        if !index.is_empty() && !cards.contains(&card) {
+            cards.push(card);
+        }
+    }
+
+    cards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::fixtures;
+    use crate::runner::MockRunner;
+    use crate::types::CheckStatus;
+
+    const APLAY: &str = "\
+card 0: PCH [HDA Intel PCH], device 0: ALC257 Analog [ALC257 Analog]
+card 1: NVidia [HDA NVidia], device 3: HDMI 0 [HDMI 0]
+";
+
+    #[test]
+    fn failed_sink_listing_is_not_a_finding() {
+        let runner = MockRunner::new()
+            .with("aplay -l", APLAY)
+            .with("pactl list cards", fixtures::LIST_CARDS)
+            .with_output("pactl list sinks", "", false);
+        let result = check_card_coverage(&runner);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert!(result.message.contains("not checked"));
+    }
+
+    #[test]
+    fn parses_unique_aplay_cards() {
+        let cards = parse_aplay_cards(APLAY);
+
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[1].index, "1");
+        assert_eq!(cards[1].name, "HDA NVidia");
+    }
+}
@@ -0,0 +1,273 @@
+//! Shared parser for `pactl list cards` output.
+
+use std::collections::BTreeMap;
+
+/// A sound card as reported by `pactl list cards`.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Card {
+    pub(super) name: String,
+    pub(super) active_profile: String,
+    pub(super) profiles: Vec<CardProfile>,
+    pub(super) ports: Vec<CardPort>,
+    pub(super) sinks: Vec<String>,
+    pub(super) properties: BTreeMap<String, String>,
+}
+
+/// One entry of a card's `Profiles:` section.
+#[derive(Debug, Clone, Default)]
+pub(super) struct CardProfile {
+    pub(super) name: String,
+    pub(super) description: String,
+    pub(super) sinks: u32,
+    pub(super) sources: u32,
+    pub(super) priority: u32,
+    pub(super) available: bool,
+}
+
+/// One entry of a card's `Ports:` section.
+#[derive(Debug, Clone, Default)]
+pub(super) struct CardPort {
+    pub(super) name: String,
+    pub(super) description: String,
+    /// `None` when the server reports "availability unknown".
+    pub(super) available: Option<bool>,
+    pub(super) profiles: Vec<String>,
+}
+
+impl Card {
+    /// Human-friendly name, falling back to the card name.
+    pub(super) fn description(&self) -> &str {
+        self.property("device.description").unwrap_or(&self.name)
+    }
+
+    /// Look up a card property such as `device.description`.
+    pub(super) fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(|v| v.as_str())
+    }
+
+    /// Returns true if `sink_name` belongs to this card.
+    ///
+    /// Cards and their sinks share the device part of the name, e.g.
+    /// `alsa_card.pci-0000_00_1f.3` and `alsa_output.pci-0000_00_1f.3.analog-stereo`.
+    pub(super) fn backs_sink(&self, sink_name: &str) -> bool {
+        let card_device = self.name.split_once('.').map(|(_, d)| d).unwrap_or("");
+        let sink_device = sink_name.split_once('.').map(|(_, d)| d).unwrap_or("");
+
+        !card_device.is_empty() && sink_device.starts_with(card_device)
+    }
+
+    pub(super) fn is_bluetooth(&self) -> bool {
+        self.name.contains("bluez") || self.name.contains("bluetooth")
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    None,
+    Properties,
+    Profiles,
+    Ports,
+    Sinks,
+}
+
+/// Parse `pactl list cards` output into cards.
+///
+/// Sections are told apart by indentation: card-level keys share the indent
+/// of `Name:`, and anything deeper belongs to the most recent section.
+pub(super) fn parse_cards(output: &str) -> Vec<Card> {
+    let mut cards: Vec<Card> = Vec::new();
+    let mut card_indent = 0;
+    let mut port_indent: Option<usize> = None;
+    let mut section = Section::None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        // New card boundary
+        if trimmed.starts_with("Name:") && (cards.is_empty() || indent <= card_indent) {
+            cards.push(Card {
+                name: trimmed
+                    .strip_prefix("Name:")
+                    .unwrap_or("")
+                    .trim()
+                    .to_string(),
+                ..Card::default()
+            });
+            card_indent = indent;
+            section = Section::None;
+            continue;
+        }
+
+        let card = match cards.last_mut() {
+            Some(card) => card,
+            None => continue,
+        };
+
+        if indent <= card_indent {
+            section = Section::None;
+            port_indent = None;
+
+            if trimmed.starts_with("Properties:") {
+                section = Section::Properties;
+            } else if trimmed.starts_with("Profiles:") {
+                section = Section::Profiles;
+            } else if trimmed.starts_with("Ports:") {
+                section = Section::Ports;
+            } else if trimmed.starts_with("Sinks:") {
+                section = Section::Sinks;
+            } else if trimmed.starts_with("Active Profile:") {
+                card.active_profile = trimmed
+                    .strip_prefix("Active Profile:")
+                    .unwrap_or("")
+                    .trim()
+                    .to_string();
+            }
+            continue;
+        }
+
+        match section {
+            Section::Properties => {
+                if let Some((key, value)) = trimmed.split_once(" = ") {
+                    card.properties
+                        .insert(key.to_string(), value.trim_matches('"').to_string());
+                }
+            }
+            Section::Profiles => {
+                if let Some(profile) = parse_profile_line(trimmed) {
+                    card.profiles.push(profile);
+                }
+            }
+            Section::Ports => {
+                let entry_indent = *port_indent.get_or_insert(indent);
+                if indent == entry_indent {
+                    if let Some(port) = parse_port_line(trimmed) {
+                        card.ports.push(port);
+                    }
+                } else if let Some(list) = trimmed.strip_prefix("Part of profile(s):") {
+                    if let Some(port) = card.ports.last_mut() {
+                        port.profiles = list.split(',').map(|p| p.trim().to_string()).collect();
+                    }
+                }
+            }
+            Section::Sinks => card.sinks.push(trimmed.to_string()),
+            Section::None => {}
+        }
+    }
+
+    cards
+}
+
+/// Split an entry like `name: Description (key: value, ...)` into its parts.
+///
+/// Profile and port names may contain colons, so the name ends at the first `": "`.
+fn split_entry(line: &str) -> Option<(&str, &str, &str)> {
+    let (name, rest) = line.split_once(": ")?;
+    let attrs_start = rest.rfind('(')?;
+    let description = rest[..attrs_start].trim();
+    let attrs = rest[attrs_start + 1..].trim_end_matches(')');
+    Some((name.trim(), description, attrs))
+}
+
+/// Parse a profile line like:
+/// `output:analog-stereo: Analog Stereo Output (sinks: 1, sources: 0, priority: 6500, available: yes)`
+fn parse_profile_line(line: &str) -> Option<CardProfile> {
+    let (name, description, attrs) = split_entry(line)?;
+
+    let mut profile = CardProfile {
+        name: name.to_string(),
+        description: description.to_string(),
+        available: true,
+        ..CardProfile::default()
+    };
+
+    for attr in attrs.split(", ") {
+        if let Some((key, value)) = attr.split_once(": ") {
+            let value = value.trim();
+            match key.trim() {
+                "sinks" => profile.sinks = value.parse().unwrap_or(0),
+                "sources" => profile.sources = value.parse().unwrap_or(0),
+                "priority" => profile.priority = value.parse().unwrap_or(0),
+                "available" => profile.available = value != "no",
+                _ => {}
+            }
+        }
+    }
+
+    Some(profile)
+}
+
+/// Parse a port line like:
+/// `analog-output-headphones: Headphones (type: Headphones, priority: 9900, not available)`
+fn parse_port_line(line: &str) -> Option<CardPort> {
+    let (name, description, attrs) = split_entry(line)?;
+
+    let available = attrs
+        .split(", ")
+        .map(|a| a.trim())
+        .find_map(|a| match a {
+            "available" => Some(Some(true)),
+            "not available" => Some(Some(false)),
+            "availability unknown" => Some(None),
+            _ => None,
+        })
+        .flatten();
+
+    Some(CardPort {
+        name: name.to_string(),
+        description: description.to_string(),
+        available,
+        profiles: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::fixtures;
+
+    #[test]
+    fn parses_profiles_ports_and_properties() {
+        let cards = parse_cards(fixtures::LIST_CARDS);
+
+        assert_eq!(cards.len(), 3);
+        let pch = &cards[0];
+        assert_eq!(pch.name, "alsa_card.pci-0000_00_1f.3");
+        assert_eq!(
+            pch.active_profile,
+            "output:analog-stereo+input:analog-stereo"
+        );
+        assert_eq!(pch.description(), "Built-in Audio");
+        assert_eq!(pch.property("alsa.card"), Some("0"));
+        assert_eq!(pch.profiles.len(), 4);
+        assert_eq!(pch.profiles[2].sinks, 1);
+        assert_eq!(pch.profiles[2].sources, 1);
+        assert_eq!(pch.profiles[2].priority, 6565);
+        assert!(!pch.profiles[3].available);
+        assert_eq!(pch.ports.len(), 3);
+        assert_eq!(pch.ports[1].available, Some(false));
+        assert_eq!(pch.ports[2].profiles, vec!["output:hdmi-stereo"]);
+    }
+
+    #[test]
+    fn bluetooth_card_lists_its_sinks() {
+        let cards = parse_cards(fixtures::LIST_CARDS);
+        let headset = &cards[2];
+
+        assert!(headset.is_bluetooth());
+        assert!(!cards[0].is_bluetooth());
+        assert_eq!(headset.sinks, vec!["bluez_output.AA_BB_CC_DD_EE_FF.1"]);
+        assert_eq!(headset.ports[0].available, None);
+    }
+
+    #[test]
+    fn cards_back_sinks_with_the_same_device() {
+        let cards = parse_cards(fixtures::LIST_CARDS);
+
+        assert!(cards[0].backs_sink("alsa_output.pci-0000_00_1f.3.analog-stereo"));
+        assert!(!cards[1].backs_sink("alsa_output.pci-0000_00_1f.3.analog-stereo"));
+    }
+}
@@ -0,0 +1,117 @@
+//! Check 8: Card Profile Selection
+//! Detects if the default sink's card has a better profile available than the active one.
+
+use super::card_parse::{parse_cards, Card};
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "card_profile";
+
+/// Check if the card backing the default sink could use a higher-priority profile.
+pub fn check_card_profile(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let default_sink_output = runner.run("pactl", &["get-default-sink"]);
+    let default_sink = default_sink_output.stdout.trim();
+
+    if default_sink.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "No card profile issues (no default sink)");
+    }
+
+    let cards_output = runner.run("pactl", &["list", "cards"]);
+    if !cards_output.success {
+        return CheckResult::ok(CHECK_NAME, "No card profile issues (cannot list cards)")
+            .with_debug(debug_info);
+    }
+
+    let cards = parse_cards(&cards_output.stdout);
+
+    // Bluetooth profiles are handled by the dedicated Bluetooth check
+    let card = match cards
+        .iter()
+        .find(|c| !c.is_bluetooth() && c.backs_sink(default_sink))
+    {
+        Some(card) => card,
+        None => {
+            return CheckResult::ok(
+                CHECK_NAME,
+                "No card profile issues (default sink has no matching card)",
+            )
+            .with_debug(debug_info)
+        }
+    };
+
+    debug_info.push_str(&format!("{}\n", describe_card(card)));
+
+    let active = card.profiles.iter().find(|p| p.name == card.active_profile);
+
+    let best = card
+        .profiles
+        .iter()
+        .filter(|p| p.available && p.sinks > 0)
+        .max_by_key(|p| p.priority);
+
+    match (active, best) {
+        (Some(active), Some(best)) if best.priority > active.priority => CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "Card '{}' uses profile '{}' but higher-priority '{}' is available",
+                card.description(),
+                active.description,
+                best.description
+            ),
+            format!(
+                "Switch profile: pactl set-card-profile {} {}",
+                card.name, best.name
+            ),
+        )
+        .with_code("card.better_profile_available")
+        .with_fix(&["pactl", "set-card-profile", &card.name, &best.name])
+        .with_debug(debug_info),
+        _ => CheckResult::ok(
+            CHECK_NAME,
+            format!(
+                "Card '{}' uses its preferred profile ({})",
+                card.description(),
+                card.active_profile
+            ),
+        )
+        .with_debug(debug_info),
+    }
+}
+
+/// Summarize a card's profiles and ports for debug output.
+fn describe_card(card: &Card) -> String {
+    let mut lines = vec![format!(
+        "card {} (active profile: {})",
+        card.name, card.active_profile
+    )];
+
+    for p in &card.profiles {
+        lines.push(format!(
+            "  profile {} \"{}\" (sinks: {}, sources: {}, priority: {}, available: {})",
+            p.name,
+            p.description,
+            p.sinks,
+            p.sources,
+            p.priority,
+            if p.available { "yes" } else { "no" }
+        ));
+    }
+
+    for port in &card.ports {
+        lines.push(format!(
+            "  port {} \"{}\" (available: {}, profiles: {})",
+            port.name,
+            port.description,
+            match port.available {
+                Some(true) => "yes",
+                Some(false) => "no",
+                None => "unknown",
+            },
+            port.profiles.join(", ")
+        ));
+    }
+
+    lines.join("\n")
+}
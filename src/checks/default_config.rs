@@ -0,0 +1,238 @@
+//! Check 28: Configured Default Sink
+//! Detects a runtime default sink that differs from the configured one and doesn't work.
+
+use std::fs;
+use std::path::PathBuf;
+
+use super::parsing::{field, last_setting};
+use super::pulse_client_config::config_candidates;
+use super::sink_names_match;
+use super::sink_validity::{is_dummy_output, parse_sink_info};
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+use crate::xdg;
+
+pub(super) const CHECK_NAME: &str = "default_sink_config";
+
+/// System-wide PulseAudio startup script, used when the user has none.
+const SYSTEM_DEFAULT_PA: &str = "/etc/pulse/default.pa";
+
+/// Compare the default sink named in the configuration with the runtime one.
+pub fn check_default_sink_config(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let info_output = runner.run("pactl", &["info"]);
+    if !info_output.success {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Configured default sink not checked (audio server unreachable)",
+        );
+    }
+    let pipewire = info_output
+        .stdout
+        .lines()
+        .find_map(|l| field(l, "Server Name"))
+        .is_some_and(|name| name.contains("PipeWire"));
+
+    let configured = configured_default_sink(pipewire);
+    let runtime_output = runner.run("pactl", &["get-default-sink"]);
+    let runtime = runtime_output.stdout.trim();
+    debug_info.push_str(&format!(
+        "configured default sink: {}\nruntime default sink: {}\n",
+        configured
+            .as_ref()
+            .map(|(sink, source)| format!("{} ({})", sink, source))
+            .unwrap_or_else(|| "(none)".to_string()),
+        if runtime.is_empty() {
+            "(none)"
+        } else {
+            runtime
+        }
+    ));
+
+    let (configured_sink, source) = match configured {
+        Some(configured) => configured,
+        None => {
+            return CheckResult::ok(CHECK_NAME, "No default sink set in the configuration")
+                .with_debug(debug_info)
+        }
+    };
+
+    // No default at all is the default_sink check's finding
+    if runtime.is_empty() || sink_names_match(runtime, &configured_sink) {
+        return CheckResult::ok(
+            CHECK_NAME,
+            format!(
+                "Default sink matches the configuration ({})",
+                configured_sink
+            ),
+        )
+        .with_debug(debug_info);
+    }
+
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
+    let problem = match parse_sink_info(&sinks_output.stdout, runtime) {
+        None => Some("missing"),
+        Some(info) if is_dummy_output(&info.name, &info.description) => Some("the Dummy Output"),
+        Some(info) if info.port_availability == "not available" => Some("unplugged"),
+        Some(_) => None,
+    };
+
+    let problem = match problem {
+        Some(problem) => problem,
+        // Choosing another working output at runtime is normal use
+        None => {
+            return CheckResult::ok(
+                CHECK_NAME,
+                format!(
+                    "Default sink '{}' differs from the configured '{}', but works",
+                    runtime, configured_sink
+                ),
+            )
+            .with_debug(debug_info)
+        }
+    };
+    debug_info.push_str(&format!("runtime default sink is {}\n", problem));
+
+    let message = format!(
+        "Default sink '{}' is {}, while '{}' is configured in {}",
+        runtime, problem, configured_sink, source
+    );
+
+    // The configured sink may itself be gone, e.g. a replaced USB headset
+    if parse_sink_info(&sinks_output.stdout, &configured_sink).is_none() {
+        return CheckResult::warning(
+            CHECK_NAME,
+            message,
+            format!(
+                "The configured sink '{}' doesn't exist either. Update or remove it in {}, and pick a working output in sound settings",
+                configured_sink, source
+            ),
+        )
+        .with_code("sink.config_mismatch")
+        .with_debug(debug_info);
+    }
+
+    CheckResult::warning(
+        CHECK_NAME,
+        message,
+        format!(
+            "Switch back to the configured sink: pactl set-default-sink {}",
+            configured_sink
+        ),
+    )
+    .with_code("sink.config_mismatch")
+    .with_fix(&["pactl", "set-default-sink", configured_sink.as_str()])
+    .with_debug(debug_info)
+}
+
+/// The configured default sink and the file it came from.
+///
+/// A `default-sink` in the client.conf libpulse uses wins, since clients
+/// follow it. Otherwise it's the default WirePlumber saved (PipeWire), or
+/// the last `set-default-sink` in default.pa (PulseAudio).
+fn configured_default_sink(pipewire: bool) -> Option<(String, String)> {
+    let client_config = std::env::var("PULSE_CLIENTCONFIG").ok();
+    let config_path = std::env::var("PULSE_CONFIG_PATH").ok();
+    let client_conf = config_candidates(client_config.as_deref(), config_path.as_deref())
+        .into_iter()
+        .find(|p| p.exists());
+
+    let from_client_conf = client_conf.and_then(|path| {
+        let contents = fs::read_to_string(&path).ok()?;
+        parse_client_default_sink(&contents).map(|sink| (sink, path.display().to_string()))
+    });
+    if from_client_conf.is_some() {
+        return from_client_conf;
+    }
+
+    let server_config = if pipewire {
+        xdg::state_home().map(|dir| dir.join("wireplumber").join("default-nodes"))
+    } else {
+        let user_default_pa = xdg::config_home()
+            .map(|dir| dir.join("pulse").join("default.pa"))
+            .filter(|path| path.is_file());
+        Some(user_default_pa.unwrap_or_else(|| PathBuf::from(SYSTEM_DEFAULT_PA)))
+    };
+
+    server_config.and_then(|path| {
+        let contents = fs::read_to_string(&path).ok()?;
+        let sink = if pipewire {
+            parse_wireplumber_default_sink(&contents)
+        } else {
+            parse_default_pa_sink(&contents)
+        };
+        sink.map(|sink| (sink, path.display().to_string()))
+    })
+}
+
+/// Returns the last `default-sink = ...` value in client.conf, ignoring
+/// `;` and `#` comments.
+fn parse_client_default_sink(contents: &str) -> Option<String> {
+    last_setting(contents, "default-sink")
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Returns the sink from a `default.configured.audio.sink=...` line in
+/// WirePlumber's saved default-nodes state.
+fn parse_wireplumber_default_sink(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .filter_map(|l| l.trim().split_once('='))
+        .filter(|(key, _)| key.trim() == "default.configured.audio.sink")
+        .map(|(_, value)| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Returns the sink from the last `set-default-sink NAME` in default.pa,
+/// ignoring `#` and `;` comments.
+fn parse_default_pa_sink(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .rev()
+        .map(|l| l.trim())
+        .filter(|l| !l.starts_with(';') && !l.starts_with('#'))
+        .filter_map(|l| l.strip_prefix("set-default-sink "))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(|sink| sink.to_string())
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_conf_default_sink_is_the_last_one_set() {
+        let contents = "default-sink = speakers\n; default-sink = hdmi\ndefault-sink = headset\n";
+        assert_eq!(
+            parse_client_default_sink(contents),
+            Some("headset".to_string())
+        );
+        assert_eq!(parse_client_default_sink("default-sink =\n"), None);
+    }
+
+    #[test]
+    fn default_pa_sink_is_the_last_set_default_sink() {
+        let contents = "\
+load-module module-udev-detect
+set-default-sink alsa_output.usb-headset
+# set-default-sink alsa_output.hdmi
+set-default-sink alsa_output.pci-0000_00_1f.3.analog-stereo
+";
+        assert_eq!(
+            parse_default_pa_sink(contents),
+            Some("alsa_output.pci-0000_00_1f.3.analog-stereo".to_string())
+        );
+    }
+
+    #[test]
+    fn wireplumber_configured_sink_is_read() {
+        let contents = "[default-nodes]\ndefault.configured.audio.sink=alsa_output.usb-headset\n";
+        assert_eq!(
+            parse_wireplumber_default_sink(contents),
+            Some("alsa_output.usb-headset".to_string())
+        );
+    }
+}
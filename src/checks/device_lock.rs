@@ -0,0 +1,158 @@
+//! Check 20: Device Lock
+//! Detects a process other than the audio server holding an ALSA playback device.
+
+use std::fs;
+
+use crate::runner::run_command;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "device_lock";
+
+/// Processes that are supposed to hold the playback devices. JACK is left to the
+/// jack_conflict check.
+const AUDIO_SERVERS: &[&str] = &[
+    "pipewire",
+    "pipewire-pulse",
+    "wireplumber",
+    "pulseaudio",
+    "jackd",
+    "jackdbus",
+];
+
+/// Check if an app has opened a playback PCM directly, locking out the audio server.
+pub fn check_device_lock() -> CheckResult {
+    let mut debug_info = String::new();
+
+    let devices = list_playback_devices();
+    if devices.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "No playback devices to check in /dev/snd");
+    }
+    let device_args: Vec<&str> = devices.iter().map(|d| d.as_str()).collect();
+
+    let mut fuser_args = vec!["-v"];
+    fuser_args.extend(&device_args);
+    let fuser_output = run_command("fuser", &fuser_args);
+
+    // fuser prints its table to stderr and exits 1 when nothing holds the files
+    let holders = if !is_missing(&fuser_output.stderr) {
+        debug_info.push_str(&format!(
+            "fuser -v /dev/snd/pcm*p:\n{}\n",
+            fuser_output.stderr
+        ));
+        parse_fuser(&fuser_output.stderr)
+    } else {
+        let lsof_output = run_command("lsof", &device_args);
+        if is_missing(&lsof_output.stderr) {
+            return CheckResult::ok(
+                CHECK_NAME,
+                "Cannot check device locks (fuser and lsof not installed)",
+            );
+        }
+        debug_info.push_str(&format!("lsof /dev/snd/pcm*p:\n{}\n", lsof_output.stdout));
+        parse_lsof(&lsof_output.stdout)
+    };
+
+    let mut held: Vec<String> = holders
+        .iter()
+        .filter(|h| !AUDIO_SERVERS.contains(&h.command.as_str()))
+        .map(|h| format!("{} (pid {}) holds {}", h.command, h.pid, h.device))
+        .collect();
+    held.dedup();
+
+    if held.is_empty() {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Playback devices are free or held by the audio server",
+        )
+        .with_debug(debug_info);
+    }
+
+    CheckResult::warning(
+        CHECK_NAME,
+        format!(
+            "Playback device opened directly, bypassing the audio server: {}",
+            held.join(", ")
+        ),
+        "Close that app, or set it to use the 'pipewire' or 'default' device instead of a hw: device",
+    )
+    .with_code("device.held_by_app")
+    .with_debug(debug_info)
+}
+
+struct DeviceHolder {
+    device: String,
+    pid: u32,
+    command: String,
+}
+
+fn is_missing(stderr: &str) -> bool {
+    stderr.contains("not found") || stderr.contains("No such file")
+}
+
+/// Playback PCM nodes; control nodes are opened by every mixer and are harmless.
+fn list_playback_devices() -> Vec<String> {
+    let mut devices: Vec<String> = match fs::read_dir("/dev/snd") {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n.starts_with("pcmC") && n.ends_with('p'))
+            .map(|n| format!("/dev/snd/{}", n))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    devices.sort();
+    devices
+}
+
+/// Parse `fuser -v` output:
+///
+/// ```text
+///                      USER        PID ACCESS COMMAND
+/// /dev/snd/pcmC0D0p:   alice      1234 F...m pipewire
+///                      alice      5678 F...m aplay
+/// ```
+fn parse_fuser(output: &str) -> Vec<DeviceHolder> {
+    let mut holders = Vec::new();
+    let mut device = String::new();
+
+    for line in output.lines() {
+        let mut rest = line;
+        if let Some((path, after)) = line.split_once(':') {
+            if path.starts_with("/dev/") {
+                device = path.to_string();
+                rest = after;
+            }
+        }
+
+        // USER PID ACCESS COMMAND
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        if fields.len() < 4 || device.is_empty() {
+            continue;
+        }
+        if let Ok(pid) = fields[1].parse() {
+            holders.push(DeviceHolder {
+                device: device.clone(),
+                pid,
+                command: fields[3..].join(" "),
+            });
+        }
+    }
+
+    holders
+}
+
+/// Parse `lsof` output: COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME.
+fn parse_lsof(output: &str) -> Vec<DeviceHolder> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some(DeviceHolder {
+                device: fields.last()?.to_string(),
+                pid: fields.get(1)?.parse().ok()?,
+                command: fields.first()?.to_string(),
+            })
+        })
+        .collect()
+}
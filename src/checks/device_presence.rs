@@ -1,14 +1,14 @@
 //! Check 2: Audio Device Presence
 //! Detects whether at least one audio card exists.
 
-use crate::runner::run_command;
+use crate::runner::CommandRunner;
 use crate::types::CheckResult;
 
-const CHECK_NAME: &str = "audio_devices";
+pub(super) const CHECK_NAME: &str = "audio_devices";
 
 /// Check if any audio devices are present on the system.
-pub fn check_audio_devices() -> CheckResult {
-    let output = run_command("aplay", &["-l"]);
+pub fn check_audio_devices(runner: &dyn CommandRunner) -> CheckResult {
+    let output = runner.run("aplay", &["-l"]);
     let debug_info = format!("aplay -l:\n{}{}", output.stdout, output.stderr);
 
     if !output.success {
@@ -19,6 +19,7 @@ pub fn check_audio_devices() -> CheckResult {
                 "Cannot check audio devices (aplay not installed)",
                 "Install alsa-utils package for full diagnostics",
             )
+            .with_code("devices.aplay_missing")
             .with_debug(debug_info);
         }
     }
@@ -32,6 +33,7 @@ pub fn check_audio_devices() -> CheckResult {
             "No audio devices detected",
             "Possible cause: missing driver or disabled device in BIOS",
         )
+        .with_code("devices.none_detected")
         .with_debug(debug_info);
     }
 
@@ -57,6 +59,7 @@ pub fn check_audio_devices() -> CheckResult {
             "No audio devices detected",
             "Possible cause: missing driver or disabled device in BIOS",
         )
+        .with_code("devices.none_detected")
         .with_debug(debug_info)
     }
 }
@@ -0,0 +1,207 @@
+//! Check 15: Kernel Log Scan
+//! Detects audio-related errors and warnings in the kernel log.
+
+use std::time::Duration;
+
+use crate::runner::{run_command_bounded, DEFAULT_MAX_OUTPUT_BYTES};
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "kernel_log";
+
+/// Words that mark a kernel log line as audio-related.
+const AUDIO_KEYWORDS: &[&str] = &["snd", "hda", "codec", "audio"];
+
+/// Firmware failures are common for Wi-Fi and GPUs too, so they only count
+/// when the line also names an audio component.
+const AUDIO_FIRMWARE_HINTS: &[&str] = &["snd", "hda", "codec", "audio", "sof", "dsp"];
+
+/// Firmware file prefixes of Sound Open Firmware DSP images, shipped
+/// separately from linux-firmware on most distros.
+const SOF_FIRMWARE_PREFIXES: &[&str] = &["intel/sof", "intel/avs", "sof-"];
+
+/// Maximum number of matching lines to keep in debug output.
+const MAX_REPORTED_LINES: usize = 20;
+
+/// Check the kernel log for audio subsystem errors and warnings.
+/// With `since`, only entries newer than that are read (`--log-since`);
+/// otherwise the whole current boot is.
+pub fn check_kernel_log(since: Option<Duration>) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let log = match since {
+        // dmesg can't filter by time, so a window always goes through the journal
+        Some(since) => {
+            let since_arg = journalctl_since(since);
+            debug_info.push_str(&format!("window: last {}s\n", since.as_secs()));
+            match read_journal(&["--since", &since_arg], &mut debug_info) {
+                Some(log) => log,
+                None => return unreadable_result(debug_info),
+            }
+        }
+        None => {
+            debug_info.push_str("window: current boot\n");
+            let dmesg_output =
+                run_command_bounded("dmesg", &["--level=err,warn"], DEFAULT_MAX_OUTPUT_BYTES);
+            if dmesg_output.success {
+                debug_info.push_str("source: dmesg --level=err,warn\n");
+                dmesg_output.stdout
+            } else {
+                // dmesg is often restricted to root; the journal may still be readable
+                debug_info.push_str(&format!("dmesg failed: {}\n", dmesg_output.stderr.trim()));
+                match read_journal(&["-b"], &mut debug_info) {
+                    Some(log) => log,
+                    None => return unreadable_result(debug_info),
+                }
+            }
+        }
+    };
+
+    let matches = find_audio_lines(&log);
+
+    if matches.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "No audio errors in the kernel log")
+            .with_debug(debug_info);
+    }
+
+    debug_info.push_str(&format!(
+        "matching lines:\n{}\n",
+        matches
+            .iter()
+            .take(MAX_REPORTED_LINES)
+            .copied()
+            .collect::<Vec<_>>()
+            .join("\n")
+    ));
+
+    // Without its firmware the DSP never comes up, so this outranks the rest
+    let missing = missing_audio_firmware(&log);
+    if !missing.is_empty() {
+        let needs_sof = missing
+            .iter()
+            .any(|file| SOF_FIRMWARE_PREFIXES.iter().any(|p| file.starts_with(p)));
+        return CheckResult::error(
+            CHECK_NAME,
+            format!("Audio firmware failed to load: {}", missing.join(", ")),
+            if needs_sof {
+                "Install the Sound Open Firmware package (sof-firmware; firmware-sof-signed on Debian/Ubuntu), then reboot"
+            } else {
+                "Install or update the linux-firmware package, then reboot"
+            },
+        )
+        .with_code("kernel_log.firmware_missing")
+        .with_debug(debug_info);
+    }
+
+    CheckResult::warning(
+        CHECK_NAME,
+        format!(
+            "{} audio-related error/warning line(s) in the kernel log",
+            matches.len()
+        ),
+        "Run with --debug to see them; driver or firmware problems often show up here",
+    )
+    .with_code("kernel_log.audio_errors")
+    .with_debug(debug_info)
+}
+
+/// Read kernel warnings and errors from the journal, limited by `window`
+/// (`-b` or `--since ...`). Returns `None` if the journal can't be read.
+fn read_journal(window: &[&str], debug_info: &mut String) -> Option<String> {
+    let mut args = vec!["-k"];
+    args.extend_from_slice(window);
+    args.extend_from_slice(&["-p", "warning", "--no-pager", "-q"]);
+
+    let journal_output = run_command_bounded("journalctl", &args, DEFAULT_MAX_OUTPUT_BYTES);
+    if !journal_output.success {
+        debug_info.push_str(&format!(
+            "journalctl -k failed: {}\n",
+            journal_output.stderr.trim()
+        ));
+        return None;
+    }
+
+    debug_info.push_str(&format!("source: journalctl {}\n", args.join(" ")));
+    Some(journal_output.stdout)
+}
+
+fn unreadable_result(debug_info: String) -> CheckResult {
+    CheckResult::warning(
+        CHECK_NAME,
+        "Cannot read the kernel log (permission denied)",
+        "Run 'sudo dmesg | grep -i snd' to check for audio driver errors",
+    )
+    .with_code("kernel_log.unreadable")
+    .with_debug(debug_info)
+}
+
+/// A `--since` value journalctl reads as "this long ago", e.g. `-600s`.
+fn journalctl_since(since: Duration) -> String {
+    format!("-{}s", since.as_secs())
+}
+
+/// Firmware files an audio driver asked for but couldn't load, from lines like
+/// `sof-audio-pci-intel-tgl 0000:00:1f.3: Direct firmware load for intel/sof/sof-tgl.ri failed with error -2`.
+/// Each file is listed once, in the order first seen.
+fn missing_audio_firmware(log: &str) -> Vec<&str> {
+    let mut files: Vec<&str> = Vec::new();
+
+    for line in log.lines() {
+        let lower = line.to_lowercase();
+        if !AUDIO_FIRMWARE_HINTS.iter().any(|k| lower.contains(k)) {
+            continue;
+        }
+
+        let file = line
+            .split_once("Direct firmware load for ")
+            .and_then(|(_, rest)| rest.split_once(" failed"))
+            .map(|(file, _)| file);
+        if let Some(file) = file {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+
+    files
+}
+
+fn find_audio_lines(log: &str) -> Vec<&str> {
+    log.lines()
+        .filter(|line| {
+            let lower = line.to_lowercase();
+            let is_audio = AUDIO_KEYWORDS.iter().any(|k| lower.contains(k));
+            let is_audio_firmware = lower.contains("firmware")
+                && AUDIO_FIRMWARE_HINTS.iter().any(|k| lower.contains(k));
+            is_audio || is_audio_firmware
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DMESG: &str = "\
+[    2.104211] sof-audio-pci-intel-tgl 0000:00:1f.3: Direct firmware load for intel/sof/sof-tgl.ri failed with error -2
+[    2.104230] sof-audio-pci-intel-tgl 0000:00:1f.3: error: request firmware intel/sof/sof-tgl.ri failed err: -2
+[    2.104298] sof-audio-pci-intel-tgl 0000:00:1f.3: Direct firmware load for intel/sof/sof-tgl.ri failed with error -2
+[    3.511902] iwlwifi 0000:00:14.3: Direct firmware load for iwlwifi-so-a0-gf-a0-72.ucode failed with error -2
+[    4.002117] snd_hda_codec_realtek hdaudioC0D0: autoconfig for ALC257: line_outs=1
+";
+
+    #[test]
+    fn finds_each_missing_audio_firmware_once() {
+        assert_eq!(missing_audio_firmware(DMESG), vec!["intel/sof/sof-tgl.ri"]);
+    }
+
+    #[test]
+    fn ignores_firmware_of_other_devices() {
+        let wifi_only = DMESG
+            .lines()
+            .filter(|l| l.contains("iwlwifi"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert!(missing_audio_firmware(&wifi_only).is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+//! Check 10: Driver Binding
+//! Detects audio hardware that is present but has no ALSA driver bound to it.
+
+use std::fs;
+
+use crate::runner::run_command;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "driver_binding";
+
+const NO_SOUNDCARDS_MARKER: &str = "--- no soundcards ---";
+
+/// Check that the kernel registered at least one sound card in `/proc/asound/cards`.
+pub fn check_driver_binding() -> CheckResult {
+    let mut debug_info = String::new();
+
+    let cards = match fs::read_to_string("/proc/asound/cards") {
+        Ok(contents) => contents,
+        Err(e) => {
+            debug_info.push_str(&format!("/proc/asound/cards: {}\n", e));
+            return CheckResult::ok(
+                CHECK_NAME,
+                "Driver binding not checked (cannot read /proc/asound/cards)",
+            )
+            .with_debug(debug_info);
+        }
+    };
+    debug_info.push_str(&format!("/proc/asound/cards:\n{}\n", cards.trim_end()));
+
+    let lspci_output = run_command("lspci", &[]);
+    let pci_audio: Vec<&str> = lspci_output
+        .stdout
+        .lines()
+        .filter(|l| l.contains("Audio device") || l.contains("Multimedia audio controller"))
+        .collect();
+    debug_info.push_str(&format!("lspci (audio):\n{}\n", pci_audio.join("\n")));
+
+    // Card entries start with the card index: " 0 [PCH            ]: HDA-Intel - ..."
+    let card_count = cards
+        .lines()
+        .filter(|l| {
+            l.split_whitespace()
+                .next()
+                .map(|t| t.parse::<u32>().is_ok())
+                .unwrap_or(false)
+        })
+        .count();
+
+    let no_cards = cards.contains(NO_SOUNDCARDS_MARKER) || card_count == 0;
+
+    if !no_cards {
+        return CheckResult::ok(
+            CHECK_NAME,
+            format!("{} sound card(s) registered with the kernel", card_count),
+        )
+        .with_debug(debug_info);
+    }
+
+    if !pci_audio.is_empty() {
+        CheckResult::error(
+            CHECK_NAME,
+            format!(
+                "Audio hardware found on PCI ({} device(s)) but no driver is bound to it",
+                pci_audio.len()
+            ),
+            "Check that the snd_hda_intel/SOF driver loads (dmesg | grep -i snd) and that required firmware is installed",
+        )
+        .with_code("driver.unbound")
+        .with_debug(debug_info)
+    } else {
+        CheckResult::error(
+            CHECK_NAME,
+            "No sound cards registered with the kernel",
+            "Possible cause: missing driver or disabled device in BIOS",
+        )
+        .with_code("driver.no_cards")
+        .with_debug(debug_info)
+    }
+}
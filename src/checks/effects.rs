@@ -0,0 +1,205 @@
+//! Check 22: Audio Effects Sink
+//! Detects EasyEffects / PulseEffects owning the default output while nothing forwards it to a device.
+
+use super::sink_inputs::parse_sink_inputs;
+use super::sink_names_match;
+use crate::runner::{CommandOutput, CommandRunner};
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "effects_sink";
+
+/// Virtual sinks created by effects apps; audio sent to them only reaches a
+/// device if the app is running and forwarding it.
+const EFFECTS_SINKS: &[&str] = &["easyeffects_sink", "ee_soe", "PulseEffects_apps"];
+
+/// Process names of the effects apps, as seen in `application.process.binary`.
+const EFFECTS_APPS: &[&str] = &["easyeffects", "pulseeffects"];
+
+/// Check whether an effects app has taken over the default output, and if so
+/// whether it still forwards audio to a real device.
+pub fn check_effects_sink(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let default_sink_output = runner.run("pactl", &["get-default-sink"]);
+    let default_sink = default_sink_output.stdout.trim();
+
+    let effects_sink = match EFFECTS_SINKS
+        .iter()
+        .find(|name| sink_names_match(name, default_sink))
+    {
+        Some(name) => name,
+        None => return CheckResult::ok(CHECK_NAME, "No audio effects sink is the default output"),
+    };
+
+    debug_info.push_str(&format!(
+        "default sink '{}' belongs to an effects app; every app's audio passes through it, so it is a likely culprit when output is silent\n",
+        effects_sink
+    ));
+
+    let inputs_output = runner.run("pactl", &["list", "sink-inputs"]);
+    if !inputs_output.success {
+        return CheckResult::ok(
+            CHECK_NAME,
+            format!(
+                "Effects sink '{}' is the default output (cannot list streams)",
+                effects_sink
+            ),
+        )
+        .with_debug(debug_info);
+    }
+
+    // The effects app plays its processed output as an ordinary stream
+    let forwarding: Vec<_> = parse_sink_inputs(&inputs_output.stdout)
+        .into_iter()
+        .filter(|input| {
+            let binary = input.process_binary.to_lowercase();
+            let app = input.app_name.to_lowercase();
+            EFFECTS_APPS
+                .iter()
+                .any(|name| binary == *name || app.contains(name))
+        })
+        .collect();
+
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
+    let real_targets: Vec<&str> = forwarding
+        .iter()
+        .filter_map(|input| {
+            input
+                .sink_index
+                .and_then(|index| sink_name_by_index(&sinks_output.stdout, index))
+                .or(input.sink_name.as_deref())
+        })
+        .filter(|name| !EFFECTS_SINKS.iter().any(|e| sink_names_match(e, name)))
+        .collect();
+
+    debug_info.push_str(&format!(
+        "effects app output stream(s): {}, forwarding to: {:?}\n",
+        forwarding.len(),
+        real_targets
+    ));
+
+    if real_targets.is_empty() {
+        // Under PipeWire the app may link straight to the device without a
+        // visible stream, so only a stopped app is proof the chain is broken
+        let lookups: Vec<(&str, CommandOutput)> = EFFECTS_APPS
+            .iter()
+            .map(|app| (*app, runner.run("pgrep", &["-x", app])))
+            .collect();
+        let running: Vec<&str> = lookups
+            .iter()
+            .filter(|(_, output)| output.success && !output.stdout.trim().is_empty())
+            .map(|(app, _)| *app)
+            .collect();
+        debug_info.push_str(&format!("effects app(s) running: {:?}\n", running));
+
+        // pgrep exits 1 when nothing matches; a lookup that never ran (pgrep
+        // missing, or absent from a dump) says nothing either way
+        let unknown = lookups
+            .iter()
+            .any(|(_, output)| output.stderr.starts_with("Failed to execute command"));
+        if running.is_empty() && unknown {
+            return CheckResult::ok(
+                CHECK_NAME,
+                format!(
+                    "Effects sink '{}' is the default output (effects app not checked)",
+                    effects_sink
+                ),
+            )
+            .with_debug(debug_info);
+        }
+
+        if !running.is_empty() {
+            return CheckResult::ok(
+                CHECK_NAME,
+                format!(
+                    "Effects sink '{}' is the default output ({} is running)",
+                    effects_sink, running[0]
+                ),
+            )
+            .with_debug(debug_info);
+        }
+
+        return CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "Default output is the effects sink '{}', but no effects app is running to forward audio to a device",
+                effects_sink
+            ),
+            "Start EasyEffects, or switch the default output to your real device",
+        )
+        .with_code("effects.output_unlinked")
+        .with_debug(debug_info);
+    }
+
+    CheckResult::ok(
+        CHECK_NAME,
+        format!(
+            "Effects sink '{}' is the default output, forwarding to '{}'",
+            effects_sink, real_targets[0]
+        ),
+    )
+    .with_debug(debug_info)
+}
+
+/// Name of the sink with `index` in `pactl list sinks` output.
+fn sink_name_by_index(output: &str, index: u32) -> Option<&str> {
+    let header = format!("Sink #{}", index);
+    let mut in_sink = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("Sink #") {
+            in_sink = trimmed == header;
+        } else if in_sink {
+            if let Some(name) = trimmed.strip_prefix("Name:") {
+                return Some(name.trim());
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::MockRunner;
+    use crate::types::CheckStatus;
+
+    fn effects_default() -> MockRunner {
+        MockRunner::new()
+            .with("pactl get-default-sink", "easyeffects_sink\n")
+            .with("pactl list sink-inputs", "")
+            .with("pactl list sinks", "")
+    }
+
+    #[test]
+    fn stopped_effects_app_leaves_the_output_unlinked() {
+        let runner = effects_default()
+            .with_output("pgrep -x easyeffects", "", false)
+            .with_output("pgrep -x pulseeffects", "", false);
+        let result = check_effects_sink(&runner);
+
+        assert_eq!(result.code.as_deref(), Some("effects.output_unlinked"));
+    }
+
+    #[test]
+    fn running_effects_app_is_ok() {
+        let runner = effects_default()
+            .with("pgrep -x easyeffects", "3141\n")
+            .with_output("pgrep -x pulseeffects", "", false);
+        let result = check_effects_sink(&runner);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert!(result.message.contains("easyeffects is running"));
+    }
+
+    #[test]
+    fn missing_process_lookup_is_not_a_finding() {
+        // A dump without pgrep output can't tell whether the app runs
+        let result = check_effects_sink(&effects_default());
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert!(result.message.contains("not checked"));
+    }
+}
@@ -0,0 +1,174 @@
+//! Captured command output shared by the check tests.
+
+/// `pactl get-default-sink` on a laptop playing through its speakers.
+pub(super) const DEFAULT_SINK: &str = "alsa_output.pci-0000_00_1f.3.analog-stereo\n";
+
+/// `pactl list sinks` with the built-in speakers (unmuted, 65%) and an HDMI
+/// output whose display is unplugged.
+pub(super) const LIST_SINKS: &str = "\
+Sink #48
+\tState: RUNNING
+\tName: alsa_output.pci-0000_00_1f.3.analog-stereo
+\tDescription: Built-in Audio Analog Stereo
+\tDriver: PipeWire
+\tSample Specification: s32le 2ch 48000Hz
+\tChannel Map: front-left,front-right
+\tOwner Module: 4294967295
+\tMute: no
+\tVolume: front-left: 42598 /  65% / -11.23 dB,   front-right: 42598 /  65% / -11.23 dB
+\t        balance 0.00
+\tBase Volume: 65536 / 100% / 0.00 dB
+\tMonitor Source: alsa_output.pci-0000_00_1f.3.analog-stereo.monitor
+\tLatency: 0 usec, configured 0 usec
+\tFlags: HARDWARE HW_MUTE_CTRL HW_VOLUME_CTRL DECIBEL_VOLUME LATENCY
+\tProperties:
+\t\talsa.card = \"0\"
+\t\talsa.card_name = \"HDA Intel PCH\"
+\t\tdevice.description = \"Built-in Audio Analog Stereo\"
+\t\tdevice.profile.name = \"analog-stereo\"
+\t\tmedia.class = \"Audio/Sink\"
+\tPorts:
+\t\tanalog-output-speaker: Speakers (type: Speaker, priority: 10000, availability group: Legacy 3, availability unknown)
+\t\tanalog-output-headphones: Headphones (type: Headphones, priority: 9900, availability group: Legacy 2, not available)
+\tActive Port: analog-output-speaker
+\tFormats:
+\t\tpcm
+
+Sink #52
+\tState: SUSPENDED
+\tName: alsa_output.pci-0000_00_1f.3.hdmi-stereo
+\tDescription: Built-in Audio Digital Stereo (HDMI)
+\tDriver: PipeWire
+\tSample Specification: s32le 2ch 48000Hz
+\tChannel Map: front-left,front-right
+\tOwner Module: 4294967295
+\tMute: no
+\tVolume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB
+\t        balance 0.00
+\tBase Volume: 65536 / 100% / 0.00 dB
+\tMonitor Source: alsa_output.pci-0000_00_1f.3.hdmi-stereo.monitor
+\tLatency: 0 usec, configured 0 usec
+\tFlags: HARDWARE DECIBEL_VOLUME LATENCY
+\tProperties:
+\t\talsa.card = \"0\"
+\t\tdevice.description = \"Built-in Audio Digital Stereo (HDMI)\"
+\t\tdevice.profile.name = \"hdmi-stereo\"
+\t\tmedia.class = \"Audio/Sink\"
+\tPorts:
+\t\thdmi-output-0: HDMI / DisplayPort (type: HDMI, priority: 5900, not available)
+\tActive Port: hdmi-output-0
+\tFormats:
+\t\tpcm
+";
+
+/// `pactl list sink-inputs` with Firefox playing to the speakers and a
+/// paused (corked) Spotify stream on the HDMI output.
+pub(super) const LIST_SINK_INPUTS: &str = "\
+Sink Input #71
+\tDriver: PipeWire
+\tOwner Module: n/a
+\tClient: 70
+\tSink: 48
+\tSample Specification: float32le 2ch 48000Hz
+\tChannel Map: front-left,front-right
+\tFormat: pcm, format.sample_format = \"\\\"float32le\\\"\"  format.rate = \"48000\"  format.channels = \"2\"  format.channel_map = \"\\\"front-left,front-right\\\"\"
+\tCorked: no
+\tMute: no
+\tVolume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB
+\t        balance 0.00
+\tBuffer Latency: 0 usec
+\tSink Latency: 0 usec
+\tResample method: PipeWire
+\tProperties:
+\t\tapplication.name = \"Firefox\"
+\t\tapplication.process.id = \"4242\"
+\t\tapplication.process.binary = \"firefox\"
+\t\tmedia.name = \"AudioStream\"
+
+Sink Input #85
+\tDriver: PipeWire
+\tOwner Module: n/a
+\tClient: 84
+\tSink: 52
+\tSample Specification: float32le 2ch 44100Hz
+\tChannel Map: front-left,front-right
+\tFormat: pcm, format.sample_format = \"\\\"float32le\\\"\"  format.rate = \"44100\"  format.channels = \"2\"  format.channel_map = \"\\\"front-left,front-right\\\"\"
+\tCorked: yes
+\tMute: no
+\tVolume: front-left: 52429 /  80% / -5.81 dB,   front-right: 52429 /  80% / -5.81 dB
+\t        balance 0.00
+\tBuffer Latency: 0 usec
+\tSink Latency: 0 usec
+\tResample method: PipeWire
+\tProperties:
+\t\tapplication.name = \"Spotify\"
+\t\tapplication.process.id = \"5151\"
+\t\tapplication.process.binary = \"spotify\"
+\t\tmedia.name = \"Spotify\"
+";
+
+/// `pactl list cards` with the built-in card playing analog stereo, an
+/// unused HDMI card on the graphics card set to `off`, and a Bluetooth
+/// headset on A2DP with SBC.
+pub(super) const LIST_CARDS: &str = "\
+Card #40
+\tName: alsa_card.pci-0000_00_1f.3
+\tDriver: alsa
+\tOwner Module: n/a
+\tProperties:
+\t\talsa.card = \"0\"
+\t\talsa.card_name = \"HDA Intel PCH\"
+\t\tdevice.description = \"Built-in Audio\"
+\t\tdevice.bus = \"pci\"
+\tProfiles:
+\t\toff: Off (sinks: 0, sources: 0, priority: 0, available: yes)
+\t\toutput:analog-stereo: Analog Stereo Output (sinks: 1, sources: 0, priority: 6500, available: yes)
+\t\toutput:analog-stereo+input:analog-stereo: Analog Stereo Duplex (sinks: 1, sources: 1, priority: 6565, available: yes)
+\t\toutput:hdmi-stereo: Digital Stereo (HDMI) Output (sinks: 1, sources: 0, priority: 5900, available: no)
+\tActive Profile: output:analog-stereo+input:analog-stereo
+\tPorts:
+\t\tanalog-output-speaker: Speakers (type: Speaker, priority: 10000, latency offset: 0 usec, availability unknown)
+\t\t\tPart of profile(s): output:analog-stereo, output:analog-stereo+input:analog-stereo
+\t\tanalog-output-headphones: Headphones (type: Headphones, priority: 9900, latency offset: 0 usec, not available)
+\t\t\tPart of profile(s): output:analog-stereo, output:analog-stereo+input:analog-stereo
+\t\thdmi-output-0: HDMI / DisplayPort (type: HDMI, priority: 5900, latency offset: 0 usec, not available)
+\t\t\tPart of profile(s): output:hdmi-stereo
+
+Card #41
+\tName: alsa_card.pci-0000_01_00.1
+\tDriver: alsa
+\tOwner Module: n/a
+\tProperties:
+\t\talsa.card = \"1\"
+\t\talsa.card_name = \"HDA NVidia\"
+\t\tdevice.description = \"GP106 High Definition Audio Controller\"
+\t\tdevice.bus = \"pci\"
+\tProfiles:
+\t\toff: Off (sinks: 0, sources: 0, priority: 0, available: yes)
+\t\toutput:hdmi-stereo: Digital Stereo (HDMI) Output (sinks: 1, sources: 0, priority: 5900, available: yes)
+\tActive Profile: off
+\tPorts:
+\t\thdmi-output-0: HDMI / DisplayPort (type: HDMI, priority: 5900, latency offset: 0 usec, availability unknown)
+\t\t\tPart of profile(s): output:hdmi-stereo
+
+Card #60
+\tName: bluez_card.AA_BB_CC_DD_EE_FF
+\tDriver: module-bluez5-device.c
+\tOwner Module: n/a
+\tProperties:
+\t\tdevice.description = \"WH-1000XM4\"
+\t\tdevice.bus = \"bluetooth\"
+\t\tapi.bluez5.address = \"AA:BB:CC:DD:EE:FF\"
+\tProfiles:
+\t\toff: Off (sinks: 0, sources: 0, priority: 0, available: yes)
+\t\ta2dp-sink-sbc: High Fidelity Playback (A2DP Sink, codec SBC) (sinks: 1, sources: 0, priority: 18, available: yes)
+\t\ta2dp-sink-aac: High Fidelity Playback (A2DP Sink, codec AAC) (sinks: 1, sources: 0, priority: 19, available: yes)
+\t\ta2dp-sink-ldac: High Fidelity Playback (A2DP Sink, codec LDAC) (sinks: 1, sources: 0, priority: 20, available: no)
+\t\theadset-head-unit: Headset Head Unit (HSP/HFP) (sinks: 1, sources: 1, priority: 1, available: yes)
+\tActive Profile: a2dp-sink-sbc
+\tPorts:
+\t\theadset-output: Headset (type: Headset, priority: 0, latency offset: 0 usec, availability unknown)
+\t\t\tPart of profile(s): a2dp-sink-sbc, a2dp-sink-aac, a2dp-sink-ldac, headset-head-unit
+\tSinks:
+\t\tbluez_output.AA_BB_CC_DD_EE_FF.1
+";
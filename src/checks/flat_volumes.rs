@@ -0,0 +1,147 @@
+//! Check 27: Flat Volumes
+//! Detects PulseAudio's flat-volumes mode dragging the output volume down to a quiet app's level.
+
+use std::fs;
+use std::path::PathBuf;
+
+use super::mute_state::parse_mute_and_volume;
+use super::parsing::{field, last_setting};
+use super::sink_inputs::parse_sink_inputs;
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+use crate::xdg;
+
+pub(super) const CHECK_NAME: &str = "flat_volumes";
+
+/// A sink or stream below this volume (on every channel) counts as turned down.
+const LOW_VOLUME_PERCENT: u32 = 30;
+
+/// System-wide PulseAudio daemon configuration, used when the user has none.
+const SYSTEM_DAEMON_CONFIG: &str = "/etc/pulse/daemon.conf";
+
+/// Drop-in directory read after the main daemon.conf.
+const SYSTEM_DAEMON_CONFIG_DIR: &str = "/etc/pulse/daemon.conf.d";
+
+/// Check whether flat volumes are holding the default sink at a quiet app's level.
+pub fn check_flat_volumes(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let info_output = runner.run("pactl", &["info"]);
+    let server_name = info_output
+        .stdout
+        .lines()
+        .find_map(|l| field(l, "Server Name"))
+        .unwrap_or("");
+    debug_info.push_str(&format!("server: {}\n", server_name));
+
+    if !info_output.success {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Flat volumes not checked (audio server unreachable)",
+        )
+        .with_debug(debug_info);
+    }
+
+    // pipewire-pulse has no flat-volumes mode
+    if server_name.contains("PipeWire") {
+        return CheckResult::ok(CHECK_NAME, "Flat volumes not in use (PipeWire)")
+            .with_debug(debug_info);
+    }
+
+    let (enabled, source) = flat_volumes_setting();
+    debug_info.push_str(&format!(
+        "flat-volumes: {} ({})\n",
+        if enabled { "yes" } else { "no" },
+        source
+    ));
+
+    if !enabled {
+        return CheckResult::ok(CHECK_NAME, "Flat volumes are off").with_debug(debug_info);
+    }
+
+    let default_sink_output = runner.run("pactl", &["get-default-sink"]);
+    let default_sink = default_sink_output.stdout.trim();
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
+    let (_, sink_volumes) = parse_mute_and_volume(&sinks_output.stdout, default_sink);
+
+    let inputs_output = runner.run("pactl", &["list", "sink-inputs"]);
+    let quiet_apps: Vec<String> = parse_sink_inputs(&inputs_output.stdout)
+        .into_iter()
+        .filter(|input| is_low(&input.volumes))
+        .map(|input| input.app_name)
+        .collect();
+
+    debug_info.push_str(&format!(
+        "default sink volume: {:?}\nquiet apps (<{}%): {}\n",
+        sink_volumes,
+        LOW_VOLUME_PERCENT,
+        quiet_apps.join(", ")
+    ));
+
+    // With flat volumes the sink follows its loudest stream, so a quiet sink
+    // next to a quiet app is the app's volume showing through
+    if is_low(&sink_volumes) && !quiet_apps.is_empty() {
+        return CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "Output volume is held low by flat volumes (following {})",
+                quiet_apps.join(", ")
+            ),
+            "Raise the app's volume, or turn flat volumes off: set 'flat-volumes = no' in ~/.config/pulse/daemon.conf and run 'pulseaudio -k'",
+        )
+        .with_code("volume.flat_volumes")
+        .with_debug(debug_info);
+    }
+
+    CheckResult::ok(
+        CHECK_NAME,
+        "Flat volumes are on, but no quiet app is holding the output down",
+    )
+    .with_debug(debug_info)
+}
+
+/// Every channel is below `LOW_VOLUME_PERCENT` (false when no volume was read).
+fn is_low(volumes: &[u32]) -> bool {
+    !volumes.is_empty() && volumes.iter().all(|&v| v < LOW_VOLUME_PERCENT)
+}
+
+/// Whether `flat-volumes` is on, and where the setting came from.
+///
+/// PulseAudio reads the user's daemon.conf instead of the system one if it
+/// exists, then the system drop-ins in name order; the last setting wins.
+/// Unset means off, the default since PulseAudio 13.
+fn flat_volumes_setting() -> (bool, String) {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    let user_config = xdg::config_home()
+        .map(|dir| dir.join("pulse").join("daemon.conf"))
+        .filter(|path| path.is_file());
+    files.push(user_config.unwrap_or_else(|| PathBuf::from(SYSTEM_DAEMON_CONFIG)));
+
+    if let Ok(entries) = fs::read_dir(SYSTEM_DAEMON_CONFIG_DIR) {
+        let mut drop_ins: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "conf"))
+            .collect();
+        drop_ins.sort();
+        files.extend(drop_ins);
+    }
+
+    let mut setting = (false, "default".to_string());
+    for path in files {
+        let value = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| parse_flat_volumes(&contents));
+        if let Some(enabled) = value {
+            setting = (enabled, path.display().to_string());
+        }
+    }
+
+    setting
+}
+
+/// Returns the last `flat-volumes = ...` value, ignoring `;` and `#` comments.
+fn parse_flat_volumes(contents: &str) -> Option<bool> {
+    last_setting(contents, "flat-volumes")
+        .map(|value| matches!(value.to_lowercase().as_str(), "yes" | "true" | "1" | "on"))
+}
@@ -0,0 +1,99 @@
+//! Check 17: JACK Conflict
+//! Detects a standalone JACK server holding the sound device away from the desktop.
+
+use std::fs;
+
+use crate::runner::run_command;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "jack_conflict";
+
+/// Standalone JACK servers. PipeWire's JACK bridge (`pw-jack`) runs clients
+/// inside PipeWire and shares the device, so it is not a conflict.
+const JACK_SERVERS: &[&str] = &["jackd", "jackdbus"];
+
+/// Check if a JACK server is running and holding an ALSA playback device.
+pub fn check_jack() -> CheckResult {
+    let mut debug_info = String::new();
+    let mut pids: Vec<u32> = Vec::new();
+
+    for server in JACK_SERVERS {
+        let pgrep_output = run_command("pgrep", &["-x", server]);
+        let server_pids: Vec<u32> = pgrep_output
+            .stdout
+            .lines()
+            .filter_map(|l| l.trim().parse().ok())
+            .collect();
+
+        if !server_pids.is_empty() {
+            debug_info.push_str(&format!("pgrep -x {}: {:?}\n", server, server_pids));
+            pids.extend(server_pids);
+        }
+    }
+
+    if pids.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "No JACK server running").with_debug(debug_info);
+    }
+
+    let held = playback_devices_owned_by(&pids);
+    debug_info.push_str(&format!("playback devices held by JACK: {:?}\n", held));
+
+    let message = if held.is_empty() {
+        "A JACK server is running and may hold the sound device".to_string()
+    } else {
+        format!("A JACK server is holding {}", held.join(", "))
+    };
+
+    CheckResult::warning(
+        CHECK_NAME,
+        message,
+        "Stop JACK (e.g. in QjackCtl), or use PipeWire's JACK support (pw-jack) instead of jackd",
+    )
+    .with_code("jack.holds_device")
+    .with_debug(debug_info)
+}
+
+/// Playback substreams whose `owner_pid` in /proc/asound is one of `pids`.
+fn playback_devices_owned_by(pids: &[u32]) -> Vec<String> {
+    let mut held = Vec::new();
+
+    let cards = match fs::read_dir("/proc/asound") {
+        Ok(entries) => entries,
+        Err(_) => return held,
+    };
+
+    for card in cards.filter_map(|e| e.ok()) {
+        let card_name = card.file_name().to_string_lossy().to_string();
+        if !card_name.starts_with("card") {
+            continue;
+        }
+
+        let pcms = match fs::read_dir(card.path()) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for pcm in pcms.filter_map(|e| e.ok()) {
+            let pcm_name = pcm.file_name().to_string_lossy().to_string();
+            if !(pcm_name.starts_with("pcm") && pcm_name.ends_with('p')) {
+                continue;
+            }
+
+            let status = fs::read_to_string(pcm.path().join("sub0/status")).unwrap_or_default();
+            let owner = status.lines().find_map(|l| {
+                l.strip_prefix("owner_pid").and_then(|rest| {
+                    rest.trim_start_matches([' ', '\t', ':'])
+                        .parse::<u32>()
+                        .ok()
+                })
+            });
+
+            if owner.map(|pid| pids.contains(&pid)).unwrap_or(false) {
+                held.push(format!("{} {}", card_name, pcm_name));
+            }
+        }
+    }
+
+    held.sort();
+    held
+}
@@ -0,0 +1,74 @@
+//! Check 14: Sound Kernel Modules
+//! Detects whether the ALSA sound modules are loaded.
+
+use std::fs;
+use std::path::Path;
+
+use crate::runner::run_command;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "kernel_modules";
+
+/// Check that `snd_pcm` and at least one other `snd_` driver module are loaded.
+pub fn check_sound_modules() -> CheckResult {
+    let mut debug_info = String::new();
+
+    // /proc/modules has the same first column as lsmod
+    let modules_list = match fs::read_to_string("/proc/modules") {
+        Ok(contents) => contents,
+        Err(_) => run_command("lsmod", &[]).stdout,
+    };
+
+    let sound_modules: Vec<&str> = modules_list
+        .lines()
+        .filter_map(|l| l.split_whitespace().next())
+        .filter(|m| m.starts_with("snd_") || *m == "snd")
+        .collect();
+
+    debug_info.push_str(&format!("sound modules: {}\n", sound_modules.join(", ")));
+
+    // Drivers built into the kernel don't show up in /proc/modules but do in /sys/module
+    let pcm_builtin = Path::new("/sys/module/snd_pcm").exists();
+    let has_pcm = sound_modules.contains(&"snd_pcm") || pcm_builtin;
+    debug_info.push_str(&format!("/sys/module/snd_pcm present: {}\n", pcm_builtin));
+
+    let drivers: Vec<&str> = sound_modules
+        .iter()
+        .copied()
+        .filter(|m| *m != "snd_pcm")
+        .collect();
+
+    if !has_pcm && drivers.is_empty() {
+        return CheckResult::error(
+            CHECK_NAME,
+            "No sound kernel modules are loaded",
+            "The audio driver didn't load. Check dmesg for errors or try: sudo modprobe snd_hda_intel",
+        )
+        .with_code("modules.none_loaded")
+        .with_debug(debug_info);
+    }
+
+    if !has_pcm {
+        return CheckResult::warning(
+            CHECK_NAME,
+            "Sound modules are loaded but snd_pcm is missing",
+            "Try loading it: sudo modprobe snd_pcm",
+        )
+        .with_code("modules.pcm_missing")
+        .with_debug(debug_info);
+    }
+
+    if drivers.is_empty() {
+        CheckResult::ok(
+            CHECK_NAME,
+            "ALSA core is loaded (drivers built into the kernel)",
+        )
+        .with_debug(debug_info)
+    } else {
+        CheckResult::ok(
+            CHECK_NAME,
+            format!("{} sound kernel module(s) loaded", sound_modules.len()),
+        )
+        .with_debug(debug_info)
+    }
+}
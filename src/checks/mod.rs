@@ -1,30 +1,749 @@
 //! Audio diagnostic checks module.
 
+mod alsa_bypass;
+mod alsa_config;
+mod alsa_mute;
 mod audio_stack;
 mod bluetooth;
+mod card_coverage;
+mod card_parse;
+mod card_profile;
+mod default_config;
+mod device_lock;
 mod device_presence;
+mod dmesg_scan;
+mod driver_binding;
+mod effects;
+#[cfg(test)]
+mod fixtures;
+mod flat_volumes;
+mod jack_conflict;
+mod kernel_modules;
 mod mute_state;
+mod parsing;
+mod permissions;
+mod pipewire_quantum;
+mod profile_off;
+mod pulse_client_config;
+mod runtime_dir;
+mod sample_rate;
+mod session_access;
 mod sink_inputs;
 mod sink_validity;
+mod snapshot;
+mod source_port;
+mod source_validity;
+mod wpctl;
+mod xruns;
 
+pub use alsa_bypass::{check_alsa_bypass, DEFAULT_DIRECT_ALSA_APPS};
+pub use alsa_config::check_alsa_config;
+pub use alsa_mute::check_alsa_mute;
 pub use audio_stack::check_audio_stack;
 pub use bluetooth::check_bluetooth_profile;
+pub use card_coverage::check_card_coverage;
+pub use card_profile::check_card_profile;
+pub use default_config::check_default_sink_config;
+pub use device_lock::check_device_lock;
 pub use device_presence::check_audio_devices;
+pub use dmesg_scan::check_kernel_log;
+pub use driver_binding::check_driver_binding;
+pub use effects::check_effects_sink;
+pub use flat_volumes::check_flat_volumes;
+pub use jack_conflict::check_jack;
+pub use kernel_modules::check_sound_modules;
 pub use mute_state::check_mute_state;
+pub use permissions::check_audio_group;
+pub use pipewire_quantum::check_pipewire_quantum;
+pub use profile_off::check_card_output_profile;
+pub use pulse_client_config::check_pulse_client_config;
+pub use runtime_dir::check_runtime_dir;
+pub use sample_rate::check_sample_rate;
+pub use session_access::check_session_access;
 pub use sink_inputs::check_sink_inputs;
 pub use sink_validity::check_default_sink;
+pub use source_port::check_source_port;
+pub use source_validity::check_default_source;
+pub use xruns::check_xruns;
 
-use crate::types::CheckResult;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
 
-/// Run all diagnostic checks in the correct order.
+use crate::runner::{CommandRunner, FileRunner, SystemRunner};
+use crate::types::{CheckInfo, CheckResult, Subsystem};
+use snapshot::SystemSnapshot;
+
+/// User-tunable inputs for the checks.
+#[derive(Debug, Clone)]
+pub struct CheckConfig {
+    /// Process names of apps that may open ALSA devices directly.
+    pub direct_alsa_apps: Vec<String>,
+    /// Sink to diagnose instead of the default one (`--sink`).
+    pub sink: Option<String>,
+    /// How far back to scan the kernel log (`--log-since`); the current boot if unset.
+    pub log_since: Option<Duration>,
+    /// Default sink that passed the checks on the previous run, used to spot
+    /// a default that silently moved (see `state::load_last_sink`).
+    pub last_sink: Option<String>,
+    /// Process whose streams the sink_inputs check looks at (`--pid`); all streams if unset.
+    pub pid: Option<u32>,
+    /// Directory of saved command output to analyze instead of this system
+    /// (`--from-dump`); only replayable checks run.
+    pub from_dump: Option<PathBuf>,
+    /// Executables run in place of particular program names (`--pactl-path`).
+    pub program_paths: Vec<(String, PathBuf)>,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            direct_alsa_apps: DEFAULT_DIRECT_ALSA_APPS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            sink: None,
+            log_since: None,
+            last_sink: None,
+            pid: None,
+            from_dump: None,
+            program_paths: Vec::new(),
+        }
+    }
+}
+
+impl CheckConfig {
+    /// A runner for the live system that honors `program_paths`.
+    pub fn system_runner(&self) -> SystemRunner {
+        SystemRunner::with_program_paths(self.program_paths.clone())
+    }
+}
+
+/// Which checks to run.
+#[derive(Debug, Clone, Default)]
+pub enum CheckFilter {
+    /// Run every check.
+    #[default]
+    All,
+    /// Run only the named checks.
+    Only(Vec<String>),
+    /// Run every check except the named ones.
+    Skip(Vec<String>),
+}
+
+impl CheckFilter {
+    fn includes(&self, name: &str) -> bool {
+        match self {
+            CheckFilter::All => true,
+            CheckFilter::Only(names) => names.iter().any(|n| n == name),
+            CheckFilter::Skip(names) => !names.iter().any(|n| n == name),
+        }
+    }
+
+    /// Returns the names in the filter that don't match any check.
+    pub fn unknown_names(&self) -> Vec<&str> {
+        match self {
+            CheckFilter::All => Vec::new(),
+            CheckFilter::Only(names) | CheckFilter::Skip(names) => names
+                .iter()
+                .map(|n| n.as_str())
+                .filter(|n| !CHECKS.iter().any(|c| c.name == *n))
+                .collect(),
+        }
+    }
+}
+
+/// Names of the sinks the audio server knows, or `None` if they can't be listed.
+pub fn sink_names(runner: &dyn CommandRunner) -> Option<Vec<String>> {
+    let output = runner.run("pactl", &["list", "short", "sinks"]);
+    if !output.success {
+        return None;
+    }
+
+    // ID<TAB>NAME<TAB>DRIVER<TAB>SPEC<TAB>STATE
+    Some(
+        output
+            .stdout
+            .lines()
+            .filter_map(|l| l.split('\t').nth(1))
+            .map(|name| name.to_string())
+            .collect(),
+    )
+}
+
+/// The audio server's current default sink, or `None` if it can't be queried.
+pub fn default_sink_name(runner: &dyn CommandRunner) -> Option<String> {
+    let output = runner.run("pactl", &["get-default-sink"]);
+    let name = output.stdout.trim();
+
+    (output.success && !name.is_empty()).then(|| name.to_string())
+}
+
+/// Names of all checks, in reporting order.
+pub fn check_names() -> Vec<&'static str> {
+    CHECKS.iter().map(|c| c.name).collect()
+}
+
+/// Every check with its description and whether `filter` enables it, in reporting order.
+pub fn list_checks(filter: &CheckFilter) -> Vec<CheckInfo> {
+    CHECKS
+        .iter()
+        .map(|c| CheckInfo {
+            name: c.name.to_string(),
+            description: c.description.to_string(),
+            subsystem: c.subsystem,
+            enabled: filter.includes(c.name),
+        })
+        .collect()
+}
+
+/// Plain-language explanation of the named check: what it inspects, why it
+/// fails, and how to fix it. `None` for an unknown name.
+pub fn check_explanation(name: &str) -> Option<&'static str> {
+    CHECKS
+        .iter()
+        .find(|c| c.name == name)
+        .map(|c| c.explanation)
+}
+
+/// Every external program the checks run, with the checks that use it,
+/// in order of first use.
+pub fn check_programs() -> Vec<(&'static str, Vec<&'static str>)> {
+    let mut programs: Vec<(&'static str, Vec<&'static str>)> = Vec::new();
+
+    for entry in CHECKS {
+        for program in entry.programs {
+            match programs.iter_mut().find(|(p, _)| p == program) {
+                Some((_, users)) => users.push(entry.name),
+                None => programs.push((program, vec![entry.name])),
+            }
+        }
+    }
+
+    programs
+}
+
+/// Where a check's fix belongs in the suggested order; lower goes first.
+///
+/// Later tiers depend on earlier ones: there is no point moving streams to a
+/// sink while the audio server is down or the card has no driver.
+pub fn fix_priority(check_name: &str) -> u8 {
+    match check_name {
+        // The audio server itself
+        runtime_dir::CHECK_NAME | audio_stack::CHECK_NAME | pulse_client_config::CHECK_NAME => 0,
+        // Hardware, drivers, and access to the device nodes
+        device_presence::CHECK_NAME
+        | driver_binding::CHECK_NAME
+        | kernel_modules::CHECK_NAME
+        | dmesg_scan::CHECK_NAME
+        | session_access::CHECK_NAME
+        | permissions::CHECK_NAME
+        | jack_conflict::CHECK_NAME
+        | device_lock::CHECK_NAME => 1,
+        // Which device and profile is in use
+        sink_validity::CHECK_NAME
+        | default_config::CHECK_NAME
+        | card_profile::CHECK_NAME
+        | profile_off::CHECK_NAME
+        | card_coverage::CHECK_NAME
+        | bluetooth::CHECK_NAME
+        | sample_rate::CHECK_NAME
+        | pipewire_quantum::CHECK_NAME
+        | xruns::CHECK_NAME
+        | effects::CHECK_NAME
+        | source_validity::CHECK_NAME
+        | source_port::CHECK_NAME => 2,
+        // Levels on that device
+        mute_state::CHECK_NAME | alsa_mute::CHECK_NAME | flat_volumes::CHECK_NAME => 3,
+        // Individual streams and apps
+        sink_inputs::CHECK_NAME | alsa_bypass::CHECK_NAME | alsa_config::CHECK_NAME => 4,
+        _ => 5,
+    }
+}
+
+/// A registered check: its name, what it looks for, a plain-language
+/// explanation for `--explain`, the external programs it runs, the part of
+/// the audio stack it belongs to, and how to run it.
+/// Checks that read shared `pactl` state get it through the runner.
+struct CheckEntry {
+    name: &'static str,
+    description: &'static str,
+    explanation: &'static str,
+    programs: &'static [&'static str],
+    subsystem: Subsystem,
+    /// Every condition code its warnings and errors can carry.
+    codes: &'static [&'static str],
+    /// Reads the system only through the runner, so it can run against a
+    /// `--from-dump` directory.
+    replayable: bool,
+    run: fn(&CheckConfig, &dyn CommandRunner) -> CheckResult,
+}
+
+/// All checks, in reporting order.
+const CHECKS: &[CheckEntry] = &[
+    CheckEntry {
+        name: runtime_dir::CHECK_NAME,
+        description: "Whether XDG_RUNTIME_DIR is set, exists, and belongs to you",
+        explanation: "Checks the user session's runtime directory (XDG_RUNTIME_DIR, normally /run/user/<uid>), where the audio server's socket and the user bus live. Over SSH, with sudo, or in a container it is often unset or points at another user's directory, so the audio server can't be reached and every later check fails with a confusing error. Run from a desktop session, or log in so systemd starts your user session and export XDG_RUNTIME_DIR=/run/user/$(id -u).",
+        programs: &[],
+        subsystem: Subsystem::Server,
+        codes: &["session.no_runtime_dir"],
+        replayable: false,
+        run: |_, _| check_runtime_dir(),
+    },
+    CheckEntry {
+        name: audio_stack::CHECK_NAME,
+        description: "Whether PipeWire, WirePlumber, or PulseAudio is running",
+        explanation: "Looks at which audio server is running: PipeWire with its session manager WirePlumber, or the older PulseAudio. Apps talk to this server, not to the sound card, so if it is stopped, crashed, or two servers fight over the card, nothing plays. Fix it by starting PipeWire and WirePlumber (systemctl --user start pipewire pipewire-pulse wireplumber) or disabling a leftover PulseAudio.",
+        programs: &["systemctl", "pactl"],
+        subsystem: Subsystem::Server,
+        codes: &["stack.pulseaudio_conflict", "stack.no_server", "stack.unclear", "stack.no_device_nodes", "stack.no_session_manager", "stack.wireplumber_inactive"],
+        replayable: false,
+        run: |_, runner| check_audio_stack(runner),
+    },
+    CheckEntry {
+        name: pulse_client_config::CHECK_NAME,
+        description: "client.conf overrides that stop clients from reaching the audio server",
+        explanation: "Reads the PulseAudio client settings (client.conf and PULSE_* environment variables) that every app uses to find the audio server. A stale 'autospawn = no' or a PULSE_SERVER pointing somewhere else makes apps fail to connect even though the server is fine. Fix it by removing the override from ~/.config/pulse/client.conf or your environment.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Server,
+        codes: &["client.autospawn_disabled", "client.config_override"],
+        replayable: false,
+        run: |_, runner| check_pulse_client_config(runner),
+    },
+    CheckEntry {
+        name: device_presence::CHECK_NAME,
+        description: "Whether at least one audio card exists",
+        explanation: "Asks ALSA (aplay -l) whether the kernel sees any sound card at all. If there is none, no setting in the desktop can help: the driver didn't load or the hardware isn't detected. Check the driver_binding, kernel_modules, and kernel_log checks for why.",
+        programs: &["aplay"],
+        subsystem: Subsystem::Hardware,
+        codes: &["devices.aplay_missing", "devices.none_detected"],
+        replayable: false,
+        run: |_, runner| check_audio_devices(runner),
+    },
+    CheckEntry {
+        name: driver_binding::CHECK_NAME,
+        description: "Audio hardware that has no ALSA driver bound to it",
+        explanation: "Compares the audio hardware on the PCI bus (lspci) with the cards ALSA knows. Hardware with no driver bound usually means a missing firmware package or a driver that failed to probe. Install the distribution's firmware packages (e.g. sof-firmware) and reboot.",
+        programs: &["lspci"],
+        subsystem: Subsystem::Hardware,
+        codes: &["driver.unbound", "driver.no_cards"],
+        replayable: false,
+        run: |_, _| check_driver_binding(),
+    },
+    CheckEntry {
+        name: kernel_modules::CHECK_NAME,
+        description: "Whether the ALSA sound modules are loaded",
+        explanation: "Checks that the core ALSA modules (snd, snd_pcm) and a sound card driver are loaded. Without them the kernel exposes no sound devices. Load the driver with modprobe, or check why it was blacklisted.",
+        programs: &["lsmod"],
+        subsystem: Subsystem::Hardware,
+        codes: &["modules.none_loaded", "modules.pcm_missing"],
+        replayable: false,
+        run: |_, _| check_sound_modules(),
+    },
+    CheckEntry {
+        name: dmesg_scan::CHECK_NAME,
+        description: "Audio-related errors and warnings in the kernel log",
+        explanation: "Scans the kernel log for audio errors such as codec probe failures or missing DSP firmware. These explain cards that exist but don't work. Missing firmware is reported with the package to install. Run with --debug to see the matching lines; --log-since limits the scan to recent entries.",
+        programs: &["dmesg", "journalctl"],
+        subsystem: Subsystem::Hardware,
+        codes: &["kernel_log.firmware_missing", "kernel_log.audio_errors", "kernel_log.unreadable"],
+        replayable: false,
+        run: |config, _| check_kernel_log(config.log_since),
+    },
+    CheckEntry {
+        name: sink_validity::CHECK_NAME,
+        description: "Whether the default sink exists, is awake, and its port is plugged in",
+        explanation: "Checks the default output device (the 'sink'): that it exists, isn't a Dummy Output, isn't stuck suspended, has its port plugged in, and for HDMI that the display accepts audio. A default pointing at unplugged headphones or a monitor without speakers is a very common cause of silence. Pick a working output in sound settings.",
+        programs: &["pactl", "wpctl"],
+        subsystem: Subsystem::Device,
+        codes: &["sink.server_unreachable", "sink.no_default", "sink.list_failed", "sink.device_unplugged", "sink.default_missing", "sink.hdmi_default_switched", "sink.hdmi_unplugged", "sink.port_unplugged", "sink.suspend_on_idle_aggressive", "sink.suspended", "sink.hdmi_no_audio", "sink.dummy_output"],
+        replayable: true,
+        run: |config, runner| {
+            check_default_sink(
+                runner,
+                config.sink.as_deref(),
+                config.last_sink.as_deref(),
+            )
+        },
+    },
+    CheckEntry {
+        name: default_config::CHECK_NAME,
+        description: "A broken runtime default sink that differs from the configured one",
+        explanation: "Compares the default output named in the configuration (default-sink in client.conf, the default WirePlumber saved, or set-default-sink in default.pa) with the one the server is using now. When they differ and the one in use is missing, unplugged, or the Dummy Output, the configured device is probably the one you want; switch back to it or fix the configuration.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Device,
+        codes: &["sink.config_mismatch"],
+        replayable: false,
+        run: |_, runner| check_default_sink_config(runner),
+    },
+    CheckEntry {
+        name: effects::CHECK_NAME,
+        description: "An EasyEffects/PulseEffects sink as default output that forwards nowhere",
+        explanation: "Checks whether EasyEffects or PulseEffects owns the default output. Their virtual sink only makes sound while the app runs and forwards audio to a real device, so a crashed effects app silences everything. Start the app again or switch the default output to your real device.",
+        programs: &["pactl", "pgrep"],
+        subsystem: Subsystem::Routing,
+        codes: &["effects.output_unlinked"],
+        replayable: true,
+        run: |_, runner| check_effects_sink(runner),
+    },
+    CheckEntry {
+        name: mute_state::CHECK_NAME,
+        description: "Whether the output is muted or its volume is too low or too high",
+        explanation: "Checks the mute switch and volume of the default output. A muted sink, every channel at 0%, very low volume, or volume above 100% (which clips) are reported. Unmute or adjust the volume in sound settings.",
+        programs: &["pactl", "wpctl"],
+        subsystem: Subsystem::Volume,
+        codes: &["mute.no_default_sink", "mute.list_failed", "mute.sink_muted", "mute.all_channels_zero", "mute.volume_low", "mute.volume_overamplified", "mute.unknown"],
+        replayable: true,
+        run: |config, runner| check_mute_state(runner, config.sink.as_deref()),
+    },
+    CheckEntry {
+        name: alsa_mute::CHECK_NAME,
+        description: "Playback controls muted at the ALSA level",
+        explanation: "Checks the ALSA mixer underneath the audio server: Master, Speaker, and Headphone switches, and the PCM softvol. These can be off or turned down even when the desktop shows full volume. Fix them in alsamixer or with amixer.",
+        programs: &["amixer"],
+        subsystem: Subsystem::Volume,
+        codes: &["alsa.amixer_missing", "alsa.control_muted", "alsa.pcm_volume_low"],
+        replayable: false,
+        run: |_, _| check_alsa_mute(),
+    },
+    CheckEntry {
+        name: flat_volumes::CHECK_NAME,
+        description: "PulseAudio flat volumes holding the output at a quiet app's level",
+        explanation: "With PulseAudio's flat-volumes option, the output volume follows the loudest app, so turning one app down drags the whole output down with it. Flags a quiet output next to a quiet app while flat volumes are on. Raise the app's volume, or set 'flat-volumes = no' in ~/.config/pulse/daemon.conf and restart PulseAudio. PipeWire doesn't have this mode.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Volume,
+        codes: &["volume.flat_volumes"],
+        replayable: false,
+        run: |_, runner| check_flat_volumes(runner),
+    },
+    CheckEntry {
+        name: sample_rate::CHECK_NAME,
+        description: "A default sink running at a sample rate its formats don't list",
+        explanation: "Compares the sample rate the default output runs at with the rates it advertises. Some USB DACs and receivers go silent or click at a rate they don't support. Set a supported rate in the PipeWire or PulseAudio configuration.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Device,
+        codes: &["rate.unsupported"],
+        replayable: true,
+        run: |_, runner| check_sample_rate(runner),
+    },
+    CheckEntry {
+        name: pipewire_quantum::CHECK_NAME,
+        description: "PipeWire clock quantum settings extreme enough to cause xruns",
+        explanation: "Reads PipeWire's clock quantum, the number of samples processed per cycle. A forced quantum that is very small causes xruns (crackling, dropouts) and a very large one adds noticeable delay. Reset it with pw-metadata -n settings 0 clock.force-quantum 0 and remove the override from your PipeWire config.",
+        programs: &["pw-metadata"],
+        subsystem: Subsystem::Server,
+        codes: &["quantum.extreme"],
+        replayable: true,
+        run: |_, runner| check_pipewire_quantum(runner),
+    },
+    CheckEntry {
+        name: xruns::CHECK_NAME,
+        description: "Xruns piling up on the default output (crackling, dropouts)",
+        explanation: "Reads PipeWire's profiler (pw-top) for the number of xruns on the default output. An xrun is a cycle where audio wasn't ready in time, heard as a crackle, click, or short dropout. A few at startup are normal; many mean the system can't keep up. Close CPU-heavy apps or raise the quantum with pw-metadata -n settings 0 clock.force-quantum 1024.",
+        programs: &["pw-top", "pactl"],
+        subsystem: Subsystem::Server,
+        codes: &["xruns.accumulating"],
+        replayable: true,
+        run: |_, runner| check_xruns(runner),
+    },
+    CheckEntry {
+        name: sink_inputs::CHECK_NAME,
+        description: "App streams that are misrouted, muted, or near-silent",
+        explanation: "Looks at the app audio streams ('sink inputs') that are playing. An app can be muted on its own, turned to 0%, or sent to a different output than the default, so only that app is silent. Fix it in the app or in pavucontrol's Playback tab.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Routing,
+        codes: &["routing.no_default_sink", "routing.list_failed", "routing.pid_no_stream", "routing.stale_default", "routing.misrouted", "routing.app_silenced"],
+        replayable: true,
+        run: |config, runner| check_sink_inputs(runner, config.sink.as_deref(), config.pid),
+    },
+    CheckEntry {
+        name: alsa_bypass::CHECK_NAME,
+        description: "Apps that open ALSA hardware directly, bypassing the audio server",
+        explanation: "Finds apps that open the sound card through ALSA directly (hw: or plughw: devices) instead of through the audio server. Such an app can lock the card for everything else, or play to a device you can't hear. Configure the app to use the 'pipewire' or 'pulse' device.",
+        programs: &["pgrep", "pactl"],
+        subsystem: Subsystem::Routing,
+        codes: &["alsa.direct_bypass"],
+        replayable: false,
+        run: |config, runner| check_alsa_bypass(&config.direct_alsa_apps, runner),
+    },
+    CheckEntry {
+        name: alsa_config::CHECK_NAME,
+        description: "An ~/.asoundrc or /etc/asound.conf that breaks the ALSA default device",
+        explanation: "Looks for custom ALSA configuration (~/.asoundrc, /etc/asound.conf) and, if there is any, checks that the ALSA 'default' device still resolves. A stale config pointing at a removed card silences apps that use ALSA directly, even while the desktop audio server works. Fix the file or rename it to test without it.",
+        programs: &["aplay"],
+        subsystem: Subsystem::Routing,
+        codes: &["alsa_config.broken_default"],
+        replayable: false,
+        run: |_, runner| check_alsa_config(runner),
+    },
+    CheckEntry {
+        name: jack_conflict::CHECK_NAME,
+        description: "A standalone JACK server holding the sound device",
+        explanation: "Checks for a standalone JACK server (jackd) holding the sound card. While it does, the desktop audio server can't use the device. Stop JACK or use PipeWire's JACK support (pw-jack) instead.",
+        programs: &["pgrep"],
+        subsystem: Subsystem::Server,
+        codes: &["jack.holds_device"],
+        replayable: false,
+        run: |_, _| check_jack(),
+    },
+    CheckEntry {
+        name: device_lock::CHECK_NAME,
+        description: "Another process holding an ALSA playback device",
+        explanation: "Checks which processes have the ALSA playback devices open. A process other than the audio server holding the device blocks it for everyone else. Close that app or configure it to play through the audio server.",
+        programs: &["fuser", "lsof"],
+        subsystem: Subsystem::Access,
+        codes: &["device.held_by_app"],
+        replayable: false,
+        run: |_, _| check_device_lock(),
+    },
+    CheckEntry {
+        name: bluetooth::CHECK_NAME,
+        description: "Bluetooth headsets stuck on HSP/HFP instead of A2DP",
+        explanation: "Checks Bluetooth headsets and speakers for the headset profile (HSP/HFP). That profile is meant for calls: it sounds muffled and is often silent for music. Switch the device to A2DP (high fidelity playback) in sound settings. In A2DP, it also reports the codec and flags plain SBC when the device offers a better one (LDAC, aptX, AAC).",
+        programs: &["pactl"],
+        subsystem: Subsystem::Bluetooth,
+        codes: &["bluetooth.headset_mode", "bluetooth.low_quality", "bluetooth.inactive_headset_mode", "bluetooth.profile_off", "bluetooth.sbc_codec"],
+        replayable: true,
+        run: |_, runner| check_bluetooth_profile(runner),
+    },
+    CheckEntry {
+        name: card_profile::CHECK_NAME,
+        description: "A card with a better profile available than the active one",
+        explanation: "Compares the active profile of the default output's card with the profiles it offers. A lower-priority profile left selected can route audio to the wrong jack or lose channels. Switch to the suggested profile in sound settings or pavucontrol's Configuration tab.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Device,
+        codes: &["card.better_profile_available"],
+        replayable: true,
+        run: |_, runner| check_card_profile(runner),
+    },
+    CheckEntry {
+        name: profile_off::CHECK_NAME,
+        description: "A sound card switched to a profile (usually 'off') with no outputs",
+        explanation: "Checks that no sound card is set to the 'off' profile (or another profile without outputs). Such a card exists but provides nothing to play to. Pick an output profile in sound settings or pavucontrol's Configuration tab.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Device,
+        codes: &["card.profile_off"],
+        replayable: true,
+        run: |_, runner| check_card_output_profile(runner),
+    },
+    CheckEntry {
+        name: card_coverage::CHECK_NAME,
+        description: "ALSA sound cards with no sink in the audio server",
+        explanation: "Compares the playback cards the kernel knows (aplay -l) with the cards PulseAudio or PipeWire turned into outputs. A card that is missing from the audio server can't be picked in sound settings even though the hardware works. This usually means the session manager (WirePlumber) skipped it; check 'pactl list cards' or restart WirePlumber.",
+        programs: &["aplay", "pactl"],
+        subsystem: Subsystem::Device,
+        codes: &["card.no_sink"],
+        replayable: true,
+        run: |_, runner| check_card_coverage(runner),
+    },
+    CheckEntry {
+        name: source_validity::CHECK_NAME,
+        description: "A missing, muted, or silent default microphone",
+        explanation: "Checks the default input device (microphone): that it exists and isn't muted or at 0%. Apps record silence otherwise. Pick and unmute a microphone in sound settings.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Device,
+        codes: &["source.server_unreachable", "source.no_default", "source.list_failed", "source.default_missing", "source.muted", "source.volume_zero"],
+        replayable: true,
+        run: |_, runner| check_default_source(runner),
+    },
+    CheckEntry {
+        name: source_port::CHECK_NAME,
+        description: "A microphone with an unplugged port or very low volume",
+        explanation: "Checks the default microphone's port and level: an external mic port with nothing plugged in, or an input volume too low to pick up sound. Plug in the mic, pick another input, or raise the input volume.",
+        programs: &["pactl"],
+        subsystem: Subsystem::Device,
+        codes: &["source.port_unplugged", "source.volume_low"],
+        replayable: true,
+        run: |_, runner| check_source_port(runner),
+    },
+    CheckEntry {
+        name: session_access::CHECK_NAME,
+        description: "Whether logind granted the session access to the sound devices",
+        explanation: "Checks that logind gave your login session access to the sound devices under /dev/snd. Without it, e.g. in a remote or inactive session, the audio server can't open the card. Log in on the local seat, or check the device ACLs.",
+        programs: &["loginctl", "getfacl"],
+        subsystem: Subsystem::Access,
+        codes: &["session.device_access_denied"],
+        replayable: false,
+        run: |_, _| check_session_access(),
+    },
+    CheckEntry {
+        name: permissions::CHECK_NAME,
+        description: "Missing 'audio' group membership where device access needs it",
+        explanation: "Checks membership of the 'audio' group on systems that rely on it for access to /dev/snd. Without logind ACLs or the group, the sound devices can't be opened. Add yourself with sudo usermod -aG audio $USER and log in again.",
+        programs: &["id"],
+        subsystem: Subsystem::Access,
+        codes: &["permissions.not_in_audio_group"],
+        replayable: false,
+        run: |_, _| check_audio_group(),
+    },
+];
+
+/// Condition codes of results made outside the registered checks: a check
+/// that panicked, and the `--probe` playback test.
+const OTHER_CODES: &[&str] = &[
+    "internal.check_panicked",
+    "probe.timed_out",
+    "probe.play_failed",
+    "probe.not_heard",
+    "probe.unconfirmed",
+];
+
+/// Every condition code a result can carry, registered checks first.
+pub fn condition_codes() -> Vec<&'static str> {
+    CHECKS
+        .iter()
+        .flat_map(|entry| entry.codes.iter().copied())
+        .chain(OTHER_CODES.iter().copied())
+        .collect()
+}
+
+/// Run the diagnostic checks selected by `filter` in the correct order.
 /// Returns results in a deterministic order for consistent reporting.
-pub fn run_all_checks() -> Vec<CheckResult> {
-    vec![
-        check_audio_stack(),
-        check_audio_devices(),
-        check_default_sink(),
-        check_mute_state(),
-        check_sink_inputs(),
-        check_bluetooth_profile(),
-    ]
+///
+/// Each check runs on its own thread; results are collected by position, not
+/// completion time. A check that panics is reported as an Error result.
+/// Commands shared between checks run once per call via a `SystemSnapshot`.
+/// Each result records how long its check took in `duration_ms`.
+pub fn run_all_checks(config: &CheckConfig, filter: &CheckFilter) -> Vec<CheckResult> {
+    let file_runner = config.from_dump.as_ref().map(FileRunner::new);
+    let system_runner = config.system_runner();
+    let inner: &dyn CommandRunner = match file_runner {
+        Some(ref runner) => runner,
+        None => &system_runner,
+    };
+    let snapshot = SystemSnapshot::new(inner);
+    let snapshot = &snapshot;
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = CHECKS
+            .iter()
+            .filter(|entry| filter.includes(entry.name))
+            .filter(|entry| entry.replayable || config.from_dump.is_none())
+            .map(|entry| {
+                let handle = scope.spawn(move || {
+                    let started = Instant::now();
+                    let mut result = (entry.run)(config, snapshot);
+                    result.duration_ms = Some(started.elapsed().as_millis() as u64);
+                    result
+                });
+                (entry, handle)
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|(entry, handle)| {
+                let mut result = handle.join().unwrap_or_else(|_| {
+                    CheckResult::error(
+                        entry.name,
+                        format!("The {} check crashed unexpectedly", entry.name),
+                        "Run with --debug and report this as a bug",
+                    )
+                    .with_code("internal.check_panicked")
+                });
+                // Keep a subsystem the check chose for a specific result
+                result.subsystem.get_or_insert(entry.subsystem);
+                result
+            })
+            .collect()
+    })
+}
+
+/// Compare two sink names taken from separate command outputs.
+///
+/// `run_command` converts output lossily, so a name containing invalid UTF-8
+/// shows up with U+FFFD replacement characters. The number of replacements
+/// can differ between commands, so runs of them are treated as equivalent.
+pub(crate) fn sink_names_match(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+
+    if !a.contains(char::REPLACEMENT_CHARACTER) && !b.contains(char::REPLACEMENT_CHARACTER) {
+        return false;
+    }
+
+    collapse_replacements(a) == collapse_replacements(b)
+}
+
+fn collapse_replacements(name: &str) -> String {
+    let mut collapsed = String::with_capacity(name.len());
+    let mut previous_was_replacement = false;
+
+    for c in name.chars() {
+        let is_replacement = c == char::REPLACEMENT_CHARACTER;
+        if !(is_replacement && previous_was_replacement) {
+            collapsed.push(c);
+        }
+        previous_was_replacement = is_replacement;
+    }
+
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sink_names_match_exactly_or_up_to_replacement_runs() {
+        assert!(sink_names_match("speakers", "speakers"));
+        assert!(!sink_names_match("speakers", "headset"));
+        assert!(sink_names_match(
+            "usb-\u{FFFD}\u{FFFD}-dac",
+            "usb-\u{FFFD}-dac"
+        ));
+        assert!(!sink_names_match("usb-\u{FFFD}-dac", "usb-\u{FFFD}-amp"));
+    }
+
+    #[test]
+    fn condition_codes_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for code in condition_codes() {
+            assert!(seen.insert(code), "{} is registered twice", code);
+        }
+    }
+
+    /// Every code literal passed to `with_code` under src/ is registered, and every
+    /// registered code is used somewhere.
+    #[test]
+    fn condition_codes_match_their_uses() {
+        fn collect_used(dir: &std::path::Path, used: &mut Vec<String>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    collect_used(&path, used);
+                } else if path.extension().is_some_and(|ext| ext == "rs") {
+                    let source = std::fs::read_to_string(&path).unwrap();
+                    for rest in source.split("with_code(\"").skip(1) {
+                        let code = rest.split('"').next().unwrap();
+                        used.push(code.to_string());
+                    }
+                }
+            }
+        }
+
+        let mut used = Vec::new();
+        collect_used(
+            &std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut used,
+        );
+        let registered = condition_codes();
+
+        for code in &used {
+            assert!(
+                registered.contains(&code.as_str()),
+                "{} is used but not registered",
+                code
+            );
+        }
+        for code in registered {
+            assert!(
+                used.iter().any(|u| u == code),
+                "{} is registered but never used",
+                code
+            );
+        }
+    }
 }
@@ -1,29 +1,51 @@
 //! Check 4: Mute State (Critical)
 //! Detects if audio is muted at the sink level.
 
-use crate::runner::run_command;
-use crate::types::CheckResult;
+use super::parsing::{field, find_block};
+use super::wpctl;
+use crate::runner::{audio_cli, AudioCli, CommandRunner};
+use crate::types::{CheckResult, Subsystem};
 
-const CHECK_NAME: &str = "mute_state";
+pub(super) const CHECK_NAME: &str = "mute_state";
+
+/// Above this, PipeWire/PulseAudio apply digital gain and audio can clip.
+const MAX_UNAMPLIFIED_VOLUME_PERCENT: u32 = 100;
+
+/// Volume the fix for a silent or near-silent sink sets: audible, without
+/// blasting whatever starts playing next.
+const RESTORE_VOLUME: &str = "50%";
 
 /// Check if audio is muted at the sink level.
-pub fn check_mute_state() -> CheckResult {
+/// Checks `target_sink` instead of the default sink when given (`--sink`).
+pub fn check_mute_state(runner: &dyn CommandRunner, target_sink: Option<&str>) -> CheckResult {
+    if audio_cli(runner) == AudioCli::Wpctl {
+        return check_mute_state_wpctl(runner);
+    }
+
     let mut debug_info = String::new();
 
     // Get default sink
-    let default_sink_output = run_command("pactl", &["get-default-sink"]);
-    let default_sink = default_sink_output.stdout.trim();
+    let default_sink_output;
+    let default_sink = match target_sink {
+        Some(sink) => sink,
+        None => {
+            default_sink_output = runner.run("pactl", &["get-default-sink"]);
+            default_sink_output.stdout.trim()
+        }
+    };
 
     if default_sink.is_empty() {
         return CheckResult::warning(
             CHECK_NAME,
             "Cannot check mute state (no default sink)",
             "Set a default output device first",
-        );
+        )
+        .with_code("mute.no_default_sink")
+        .with_subsystem(Subsystem::Server);
     }
 
     // Get sink mute status
-    let sinks_output = run_command("pactl", &["list", "sinks"]);
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
     debug_info.push_str(&format!(
         "pactl list sinks (mute info):\n{}\n",
         sinks_output
@@ -33,6 +55,7 @@ pub fn check_mute_state() -> CheckResult {
             .collect::<Vec<_>>()
             .join("\n")
     ));
+    debug_info.push_str(&sinks_output.utf8_note("pactl list sinks"));
 
     if !sinks_output.success {
         return CheckResult::warning(
@@ -40,11 +63,70 @@ pub fn check_mute_state() -> CheckResult {
             "Cannot check mute state",
             "Ensure audio server is running",
         )
+        .with_code("mute.list_failed")
+        .with_subsystem(Subsystem::Server)
         .with_debug(debug_info);
     }
 
     // Parse sink info to find mute status and volume
-    let (is_muted, volume_percent) = parse_mute_and_volume(&sinks_output.stdout, default_sink);
+    let (is_muted, channel_volumes) = parse_mute_and_volume(&sinks_output.stdout, default_sink);
+    let sink_ref = target_sink.unwrap_or("@DEFAULT_SINK@");
+    evaluate_mute(
+        is_muted,
+        &channel_volumes,
+        &["pactl", "set-sink-mute", sink_ref, "0"],
+        &["pactl", "set-sink-volume", sink_ref],
+        debug_info,
+    )
+}
+
+/// Check mute state through `wpctl` when `pactl` is not installed.
+fn check_mute_state_wpctl(runner: &dyn CommandRunner) -> CheckResult {
+    let output = runner.run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"]);
+    let debug_info = format!(
+        "wpctl get-volume @DEFAULT_AUDIO_SINK@:\n{}{}\n",
+        output.stdout, output.stderr
+    );
+
+    if !output.success {
+        return CheckResult::warning(
+            CHECK_NAME,
+            "Cannot check mute state",
+            "Ensure audio server is running",
+        )
+        .with_code("mute.list_failed")
+        .with_subsystem(Subsystem::Server)
+        .with_debug(debug_info);
+    }
+
+    let (is_muted, volume) = wpctl::parse_get_volume(&output.stdout);
+    let channel_volumes: Vec<u32> = volume.into_iter().collect();
+
+    evaluate_mute(
+        is_muted,
+        &channel_volumes,
+        &["wpctl", "set-mute", "@DEFAULT_AUDIO_SINK@", "0"],
+        &["wpctl", "set-volume", "@DEFAULT_AUDIO_SINK@"],
+        debug_info,
+    )
+}
+
+/// Turn the parsed mute flag and channel volumes into a result.
+/// `unmute_fix` unmutes the sink; `set_volume` sets its volume once given a
+/// percentage as the last argument.
+fn evaluate_mute(
+    is_muted: Option<bool>,
+    channel_volumes: &[u32],
+    unmute_fix: &[&str],
+    set_volume: &[&str],
+    debug_info: String,
+) -> CheckResult {
+    let restore_fix = [set_volume, &[RESTORE_VOLUME]].concat();
+    let max_volume = format!("{}%", MAX_UNAMPLIFIED_VOLUME_PERCENT);
+    let lower_fix = [set_volume, &[max_volume.as_str()]].concat();
+
+    // The loudest channel decides whether anything is audible
+    let volume_percent = channel_volumes.iter().max().copied();
 
     match is_muted {
         Some(true) => CheckResult::error(
@@ -52,8 +134,25 @@ pub fn check_mute_state() -> CheckResult {
             "Output is muted",
             "Unmute in sound settings or press the mute key",
         )
+        .with_code("mute.sink_muted")
+        .with_fix(unmute_fix)
         .with_debug(debug_info),
         Some(false) => {
+            // Every channel at zero is silent without being muted
+            if !channel_volumes.is_empty() && channel_volumes.iter().all(|&v| v == 0) {
+                return CheckResult::error(
+                    CHECK_NAME,
+                    format!(
+                        "All {} output channel(s) are at 0% volume (not muted, but silent)",
+                        channel_volumes.len()
+                    ),
+                    format!("Restore the volume: {}", restore_fix.join(" ")),
+                )
+                .with_code("mute.all_channels_zero")
+                .with_fix(&restore_fix)
+                .with_debug(debug_info);
+            }
+
             // Check for very low volume
             if let Some(vol) = volume_percent {
                 if vol < 5 {
@@ -62,13 +161,42 @@ pub fn check_mute_state() -> CheckResult {
                         format!("Volume is very low ({}%)", vol),
                         "Increase volume in sound settings",
                     )
+                    .with_code("mute.volume_low")
+                    .with_fix(&restore_fix)
                     .with_debug(debug_info);
                 }
-                CheckResult::ok(
-                    CHECK_NAME,
-                    format!("Output is not muted (volume: {}%)", vol),
-                )
-                .with_debug(debug_info)
+
+                if vol > MAX_UNAMPLIFIED_VOLUME_PERCENT {
+                    return CheckResult::warning(
+                        CHECK_NAME,
+                        format!(
+                            "Volume is above {}% ({}%), audio may distort or clip",
+                            MAX_UNAMPLIFIED_VOLUME_PERCENT, vol
+                        ),
+                        format!(
+                            "Lower the volume to avoid clipping: {}",
+                            lower_fix.join(" ")
+                        ),
+                    )
+                    .with_code("mute.volume_overamplified")
+                    .with_fix(&lower_fix)
+                    .with_debug(debug_info);
+                }
+
+                let message = if channel_volumes.iter().any(|&v| v != vol) {
+                    format!(
+                        "Output is not muted (volume: {}%, channels: {})",
+                        vol,
+                        channel_volumes
+                            .iter()
+                            .map(|v| format!("{}%", v))
+                            .collect::<Vec<_>>()
+                            .join(" / ")
+                    )
+                } else {
+                    format!("Output is not muted (volume: {}%)", vol)
+                };
+                CheckResult::ok(CHECK_NAME, message).with_debug(debug_info)
             } else {
                 CheckResult::ok(CHECK_NAME, "Output is not muted").with_debug(debug_info)
             }
@@ -78,57 +206,208 @@ pub fn check_mute_state() -> CheckResult {
             "Could not determine mute state",
             "Check sound settings manually",
         )
+        .with_code("mute.unknown")
         .with_debug(debug_info),
     }
 }
 
-fn parse_mute_and_volume(output: &str, target_sink: &str) -> (Option<bool>, Option<u32>) {
-    let mut in_target_sink = false;
-    let mut muted: Option<bool> = None;
-    let mut volume: Option<u32> = None;
+/// Returns the mute flag and the per-channel volume percentages of the target sink.
+/// Also works on `pactl list sources`, which uses the same layout.
+pub(super) fn parse_mute_and_volume(output: &str, target_sink: &str) -> (Option<bool>, Vec<u32>) {
+    let block = match find_block(output, target_sink) {
+        Some(block) => block,
+        None => return (None, Vec::new()),
+    };
 
-    for line in output.lines() {
-        let trimmed = line.trim();
+    let muted = block
+        .lines()
+        .find_map(|l| field(l, "Mute"))
+        .map(|v| v.eq_ignore_ascii_case("yes"));
 
-        if trimmed.starts_with("Name:") {
-            let name = trimmed.strip_prefix("Name:").unwrap_or("").trim();
-            in_target_sink = name == target_sink;
-            if !in_target_sink {
-                // Reset if we're in a different sink
-                continue;
-            }
-        }
+    // Parse every channel percentage from a line like:
+    // Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB
+    let volumes = block
+        .lines()
+        .find(|l| field(l, "Volume").is_some())
+        .map(|l| parse_channel_percentages(l.trim()))
+        .unwrap_or_default();
 
-        if !in_target_sink {
-            continue;
-        }
+    (muted, volumes)
+}
 
-        if trimmed.starts_with("Mute:") {
-            let mute_value = trimmed.strip_prefix("Mute:").unwrap_or("").trim();
-            muted = Some(mute_value.eq_ignore_ascii_case("yes"));
-        }
+/// Extract each `N%` value from a `Volume:` line, in channel order.
+pub(super) fn parse_channel_percentages(line: &str) -> Vec<u32> {
+    let mut percentages = Vec::new();
 
-        if trimmed.starts_with("Volume:") && volume.is_none() {
-            // Parse volume percentage from a line like:
-            // Volume: front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB
-            if let Some(percent_pos) = trimmed.find('%') {
-                // Find the number before the %
-                let before_percent = &trimmed[..percent_pos];
-                let num_start = before_percent
-                    .rfind(|c: char| !c.is_ascii_digit())
-                    .map(|i| i + 1)
-                    .unwrap_or(0);
-                if let Ok(vol) = before_percent[num_start..].parse::<u32>() {
-                    volume = Some(vol);
-                }
-            }
+    for (percent_pos, _) in line.match_indices('%') {
+        // Find the number before the %
+        let before_percent = &line[..percent_pos];
+        let num_start = before_percent
+            .rfind(|c: char| !c.is_ascii_digit())
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        if let Ok(vol) = before_percent[num_start..].parse::<u32>() {
+            percentages.push(vol);
         }
+    }
 
-        // Stop after we have both values
-        if muted.is_some() && volume.is_some() {
-            break;
-        }
+    percentages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::fixtures;
+    use crate::runner::MockRunner;
+    use crate::types::CheckStatus;
+
+    fn pactl(sinks: &str) -> MockRunner {
+        MockRunner::new()
+            .with("pactl get-default-sink", fixtures::DEFAULT_SINK)
+            .with("pactl list sinks", sinks)
+    }
+
+    #[test]
+    fn unmuted_default_sink_reports_its_volume() {
+        let result = check_mute_state(&pactl(fixtures::LIST_SINKS), None);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert_eq!(result.message, "Output is not muted (volume: 65%)");
+    }
+
+    #[test]
+    fn muted_default_sink_is_an_error_with_an_unmute_fix() {
+        let sinks = fixtures::LIST_SINKS.replacen("Mute: no", "Mute: yes", 1);
+        let result = check_mute_state(&pactl(&sinks), None);
+
+        assert_eq!(result.status, CheckStatus::Error);
+        assert_eq!(result.code.as_deref(), Some("mute.sink_muted"));
+        assert_eq!(
+            result.fix_command,
+            Some(vec![
+                "pactl".to_string(),
+                "set-sink-mute".to_string(),
+                "@DEFAULT_SINK@".to_string(),
+                "0".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn only_the_default_sink_counts() {
+        // The HDMI sink in the fixture is at 100%; muting it changes nothing
+        let sinks = fixtures::LIST_SINKS.replace(
+            "Mute: no\n\tVolume: front-left: 65536",
+            "Mute: yes\n\tVolume: front-left: 65536",
+        );
+        let result = check_mute_state(&pactl(&sinks), None);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn target_sink_is_checked_instead_of_the_default() {
+        let sinks = fixtures::LIST_SINKS.replace(
+            "Mute: no\n\tVolume: front-left: 65536",
+            "Mute: yes\n\tVolume: front-left: 65536",
+        );
+        let result = check_mute_state(
+            &pactl(&sinks),
+            Some("alsa_output.pci-0000_00_1f.3.hdmi-stereo"),
+        );
+
+        assert_eq!(result.code.as_deref(), Some("mute.sink_muted"));
+    }
+
+    #[test]
+    fn low_volume_fix_restores_a_moderate_level() {
+        let sinks = fixtures::LIST_SINKS.replacen("42598 /  65%", "1966 /   3%", 2);
+        let result = check_mute_state(&pactl(&sinks), None);
+
+        assert_eq!(result.code.as_deref(), Some("mute.volume_low"));
+        assert_eq!(
+            result.fix_command,
+            Some(vec![
+                "pactl".to_string(),
+                "set-sink-volume".to_string(),
+                "@DEFAULT_SINK@".to_string(),
+                "50%".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn overamplified_fix_lowers_to_full_volume() {
+        let sinks = fixtures::LIST_SINKS.replacen("42598 /  65%", "98304 / 150%", 2);
+        let result = check_mute_state(&pactl(&sinks), None);
+
+        assert_eq!(result.code.as_deref(), Some("mute.volume_overamplified"));
+        assert_eq!(
+            result.fix_command.as_ref().and_then(|cmd| cmd.last()),
+            Some(&"100%".to_string())
+        );
+    }
+
+    #[test]
+    fn falls_back_to_wpctl_without_pactl() {
+        let runner = MockRunner::new().with(
+            "wpctl get-volume @DEFAULT_AUDIO_SINK@",
+            "Volume: 0.40 [MUTED]\n",
+        );
+        let result = check_mute_state(&runner, None);
+
+        assert_eq!(result.code.as_deref(), Some("mute.sink_muted"));
+    }
+
+    #[test]
+    fn failed_listing_is_a_warning() {
+        let runner = MockRunner::new()
+            .with("pactl get-default-sink", fixtures::DEFAULT_SINK)
+            .with_output("pactl list sinks", "", false);
+        let result = check_mute_state(&runner, None);
+
+        assert_eq!(result.code.as_deref(), Some("mute.list_failed"));
     }
 
-    (muted, volume)
+    #[test]
+    fn all_channels_at_zero_is_an_error_with_a_restore_fix() {
+        let sinks = fixtures::LIST_SINKS.replacen("42598 /  65%", "0 /   0%", 2);
+        let result = check_mute_state(&pactl(&sinks), None);
+
+        assert_eq!(result.status, CheckStatus::Error);
+        assert_eq!(result.code.as_deref(), Some("mute.all_channels_zero"));
+        assert!(result.message.starts_with("All 2 output channel(s)"));
+        assert_eq!(
+            result.fix_command.as_ref().and_then(|cmd| cmd.last()),
+            Some(&RESTORE_VOLUME.to_string())
+        );
+    }
+
+    #[test]
+    fn one_channel_at_zero_is_not_silent() {
+        let sinks = fixtures::LIST_SINKS.replacen("42598 /  65%", "0 /   0%", 1);
+        let result = check_mute_state(&pactl(&sinks), None);
+
+        assert_eq!(result.code, None);
+    }
+
+    #[test]
+    fn loudest_channel_decides_the_volume() {
+        let sinks = fixtures::LIST_SINKS.replacen("42598 /  65%", "1966 /   3%", 1);
+        let result = check_mute_state(&pactl(&sinks), None);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert_eq!(
+            result.message,
+            "Output is not muted (volume: 65%, channels: 3% / 65%)"
+        );
+    }
+
+    #[test]
+    fn parses_every_channel_percentage() {
+        let line =
+            "Volume: front-left: 42598 /  65% / -11.23 dB,   front-right: 19661 /  30% / -31.37 dB";
+
+        assert_eq!(parse_channel_percentages(line), vec![65, 30]);
+    }
 }
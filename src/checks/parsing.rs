@@ -0,0 +1,97 @@
+//! Shared helpers for the `Key: value` fields, `key = "value"` properties,
+//! and per-object blocks of `pactl list` output, and for `key = value`
+//! config files.
+
+use super::sink_names_match;
+
+/// The trimmed value of a `Key: value` line, e.g. `field("\tMute: no", "Mute")`.
+pub(super) fn field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    line.trim()
+        .strip_prefix(key)?
+        .strip_prefix(':')
+        .map(str::trim)
+}
+
+/// The unquoted value of a `key = "value"` property line,
+/// e.g. `property("\t\talsa.card = \"0\"", "alsa.card")`.
+pub(super) fn property<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    line.trim()
+        .strip_prefix(key)?
+        .strip_prefix(" = ")
+        .map(|value| value.trim_matches('"'))
+}
+
+/// One object in `pactl list` output: everything from its unindented header
+/// line (`Sink #3`, `Source #7`, ...) up to the next header.
+pub(super) struct Block<'a> {
+    /// The object's `Name:` field, empty if it has none.
+    pub(super) name: &'a str,
+    pub(super) text: &'a str,
+}
+
+/// Split `pactl list` output into per-object blocks, keyed by `Name:`.
+pub(super) fn blocks(output: &str) -> Vec<Block<'_>> {
+    let mut starts: Vec<usize> = Vec::new();
+    let mut offset = 0;
+
+    for line in output.split_inclusive('\n') {
+        if !line.trim().is_empty() && !line.starts_with(char::is_whitespace) {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(output.len());
+            let text = &output[start..end];
+            let name = text
+                .lines()
+                .find_map(|line| field(line, "Name"))
+                .unwrap_or("");
+            Block { name, text }
+        })
+        .collect()
+}
+
+/// The block of the object named `name` (compared as in `sink_names_match`).
+pub(super) fn find_block<'a>(output: &'a str, name: &str) -> Option<&'a str> {
+    blocks(output)
+        .into_iter()
+        .find(|block| sink_names_match(block.name, name))
+        .map(|block| block.text)
+}
+
+/// The value of the last `key = value` line for `key` in a PulseAudio-style
+/// config file (daemon.conf, client.conf), ignoring `;` and `#` comments.
+pub(super) fn last_setting<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+    contents
+        .lines()
+        .rev()
+        .map(|l| l.trim())
+        .filter(|l| !l.starts_with(';') && !l.starts_with('#'))
+        .filter_map(|l| l.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, value)| value.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_setting_wins_and_comments_are_skipped() {
+        let contents = "\
+flat-volumes = yes
+; flat-volumes = no
+# flat-volumes = no
+  flat-volumes=no
+autospawn = yes
+";
+        assert_eq!(last_setting(contents, "flat-volumes"), Some("no"));
+        assert_eq!(last_setting(contents, "autospawn"), Some("yes"));
+        assert_eq!(last_setting(contents, "default-sink"), None);
+    }
+}
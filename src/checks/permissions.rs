@@ -0,0 +1,84 @@
+//! Check 16: Audio Group Membership
+//! Detects if the user lacks the 'audio' group on systems that rely on it for device access.
+
+use std::fs::{self, File};
+
+use crate::runner::run_command;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "audio_group";
+
+/// Check whether the current user is in the `audio` group.
+///
+/// With logind ACLs the group is usually unnecessary, so missing membership
+/// only matters when the sound control devices can't be opened.
+pub fn check_audio_group() -> CheckResult {
+    let mut debug_info = String::new();
+
+    let id_output = run_command("id", &["-nG"]);
+    debug_info.push_str(&format!(
+        "id -nG:\n{}{}\n",
+        id_output.stdout, id_output.stderr
+    ));
+
+    if !id_output.success {
+        return CheckResult::ok(CHECK_NAME, "Cannot check group membership (id failed)")
+            .with_debug(debug_info);
+    }
+
+    if id_output.stdout.split_whitespace().any(|g| g == "audio") {
+        return CheckResult::ok(CHECK_NAME, "User is in the 'audio' group").with_debug(debug_info);
+    }
+
+    let controls = list_control_devices();
+    let inaccessible: Vec<&str> = controls
+        .iter()
+        .filter(|path| File::open(path).is_err())
+        .map(|path| path.as_str())
+        .collect();
+    debug_info.push_str(&format!(
+        "control devices: {:?}\ninaccessible: {:?}\n",
+        controls, inaccessible
+    ));
+
+    if controls.is_empty() {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "User is not in the 'audio' group (no sound control devices to check)",
+        )
+        .with_debug(debug_info);
+    }
+
+    if inaccessible.is_empty() {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "User is not in the 'audio' group (not needed, devices are accessible)",
+        )
+        .with_debug(debug_info);
+    }
+
+    CheckResult::warning(
+        CHECK_NAME,
+        format!(
+            "User is not in the 'audio' group and cannot open {}",
+            inaccessible.join(", ")
+        ),
+        "Add your user to the group: sudo usermod -aG audio $USER (then log out and back in)",
+    )
+    .with_code("permissions.not_in_audio_group")
+    .with_debug(debug_info)
+}
+
+/// Control nodes are safe to open: unlike PCM nodes they are never held exclusively.
+fn list_control_devices() -> Vec<String> {
+    let mut devices: Vec<String> = match fs::read_dir("/dev/snd") {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("controlC"))
+            .map(|e| e.path().display().to_string())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    devices.sort();
+    devices
+}
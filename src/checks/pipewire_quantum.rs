@@ -0,0 +1,110 @@
+//! Check 21: PipeWire Quantum
+//! Detects clock quantum settings extreme enough to cause xruns or huge latency.
+
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "pipewire_quantum";
+
+/// Below this many samples per cycle, the graph rarely finishes in time.
+const MIN_SANE_QUANTUM: u32 = 16;
+
+/// Above this many samples per cycle, latency becomes audible and some
+/// devices can't buffer a whole cycle.
+const MAX_SANE_QUANTUM: u32 = 8192;
+
+/// Settings read from the `settings` metadata, in reporting order.
+const QUANTUM_KEYS: &[&str] = &[
+    "clock.quantum",
+    "clock.min-quantum",
+    "clock.max-quantum",
+    "clock.force-quantum",
+];
+
+/// Check PipeWire's clock quantum settings for extreme values.
+pub fn check_pipewire_quantum(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let output = runner.run("pw-metadata", &["-n", "settings"]);
+    debug_info.push_str(&format!(
+        "pw-metadata -n settings:\n{}{}\n",
+        output.stdout, output.stderr
+    ));
+
+    if output.stderr.contains("not found") || output.stderr.contains("No such file") {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Quantum not checked (pw-metadata not installed)",
+        )
+        .with_debug(debug_info);
+    }
+
+    if !output.success {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Quantum not checked (PipeWire settings unavailable)",
+        )
+        .with_debug(debug_info);
+    }
+
+    let settings = parse_settings(&output.stdout);
+
+    // A force-quantum of 0 means "not forced"
+    let extreme: Vec<String> = QUANTUM_KEYS
+        .iter()
+        .filter_map(|key| {
+            let value = settings.iter().find(|(k, _)| k == key)?.1;
+            let out_of_range = !(MIN_SANE_QUANTUM..=MAX_SANE_QUANTUM).contains(&value);
+            let unset_force = *key == "clock.force-quantum" && value == 0;
+            (out_of_range && !unset_force).then(|| format!("{} = {}", key, value))
+        })
+        .collect();
+
+    if !extreme.is_empty() {
+        return CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "PipeWire clock quantum is outside {}–{} samples ({})",
+                MIN_SANE_QUANTUM,
+                MAX_SANE_QUANTUM,
+                extreme.join(", ")
+            ),
+            "Very small quanta cause xruns (crackling, dropouts); very large ones add seconds of latency. Reset with: pw-metadata -n settings 0 clock.force-quantum 0, and remove the override from ~/.config/pipewire/pipewire.conf.d/",
+        )
+        .with_code("quantum.extreme")
+        .with_debug(debug_info);
+    }
+
+    match settings.iter().find(|(k, _)| k == "clock.quantum") {
+        Some((_, quantum)) => CheckResult::ok(
+            CHECK_NAME,
+            format!("PipeWire clock quantum is {} samples", quantum),
+        )
+        .with_debug(debug_info),
+        None => CheckResult::ok(CHECK_NAME, "Quantum not checked (not reported by PipeWire)")
+            .with_debug(debug_info),
+    }
+}
+
+/// Extract the numeric quantum settings from `pw-metadata` output lines like:
+/// `update: id:0 key:'clock.quantum' value:'1024' type:''`
+fn parse_settings(output: &str) -> Vec<(String, u32)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let key = quoted_after(line, "key:'")?;
+            if !QUANTUM_KEYS.contains(&key) {
+                return None;
+            }
+            let value = quoted_after(line, "value:'")?.parse().ok()?;
+            Some((key.to_string(), value))
+        })
+        .collect()
+}
+
+/// The text between `prefix` and the next single quote.
+fn quoted_after<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    let start = line.find(prefix)? + prefix.len();
+    let len = line[start..].find('\'')?;
+    Some(&line[start..start + len])
+}
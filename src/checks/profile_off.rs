@@ -0,0 +1,159 @@
+//! Check 23: Card Output Profile
+//! Detects sound cards switched to a profile (usually `off`) that provides no output at all.
+
+use super::card_parse::{parse_cards, Card, CardProfile};
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "card_output_profile";
+
+/// Check that the card behind the default output has an active profile with
+/// at least one sink, or failing that that some card does.
+pub fn check_card_output_profile(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let default_sink_output = runner.run("pactl", &["get-default-sink"]);
+    let default_sink = default_sink_output.stdout.trim();
+
+    let cards_output = runner.run("pactl", &["list", "cards"]);
+    if !cards_output.success {
+        return CheckResult::ok(CHECK_NAME, "No output profile issues (cannot list cards)")
+            .with_debug(debug_info);
+    }
+
+    let cards = parse_cards(&cards_output.stdout);
+    for card in &cards {
+        debug_info.push_str(&format!(
+            "card {}: active profile '{}', {} sink(s)\n",
+            card.name,
+            card.active_profile,
+            card.sinks.len()
+        ));
+    }
+
+    // A card whose profile provides no sinks can't be backing the default
+    // sink, so any card (Bluetooth included) that backs it is enough
+    if !default_sink.is_empty() && cards.iter().any(|c| c.backs_sink(default_sink)) {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "The default output's card has an output profile",
+        )
+        .with_debug(debug_info);
+    }
+
+    // Bluetooth profiles are handled by the dedicated Bluetooth check
+    let (silent, with_output): (Vec<&Card>, Vec<&Card>) = cards
+        .iter()
+        .filter(|c| !c.is_bluetooth())
+        .partition(|card| card.active_profile == "off" || active_sinks(card) == 0);
+
+    // A virtual default (effects, combine) can forward to any card that
+    // still has an output, so an unused card switched off is deliberate
+    if !with_output.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "At least one card has an output profile")
+            .with_debug(debug_info);
+    }
+
+    let silent = silent
+        .into_iter()
+        .find_map(|card| best_output_profile(card).map(|best| (card, best)));
+
+    match silent {
+        Some((card, best)) => CheckResult::error(
+            CHECK_NAME,
+            format!(
+                "Card '{}' is set to profile '{}', which provides no outputs",
+                card.description(),
+                card.active_profile
+            ),
+            format!(
+                "Pick an output profile in sound settings or pavucontrol (Configuration tab), e.g.: pactl set-card-profile {} {}",
+                card.name, best.name
+            ),
+        )
+        .with_code("card.profile_off")
+        .with_fix(&["pactl", "set-card-profile", &card.name, &best.name])
+        .with_debug(debug_info),
+        None => CheckResult::ok(CHECK_NAME, "No card is left without an output profile")
+            .with_debug(debug_info),
+    }
+}
+
+/// How many sinks the active profile of `card` provides.
+fn active_sinks(card: &Card) -> u32 {
+    card.profiles
+        .iter()
+        .find(|p| p.name == card.active_profile)
+        .map(|p| p.sinks)
+        .unwrap_or(0)
+}
+
+/// The highest-priority available profile of `card` that provides a sink.
+fn best_output_profile(card: &Card) -> Option<&CardProfile> {
+    card.profiles
+        .iter()
+        .filter(|p| p.available && p.sinks > 0)
+        .max_by_key(|p| p.priority)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::fixtures;
+    use crate::runner::MockRunner;
+    use crate::types::CheckStatus;
+
+    fn pactl(default_sink: &str, cards: &str) -> MockRunner {
+        MockRunner::new()
+            .with("pactl get-default-sink", default_sink)
+            .with("pactl list cards", cards)
+    }
+
+    /// The fixture with the built-in card switched off as well.
+    fn all_off() -> String {
+        fixtures::LIST_CARDS.replacen(
+            "Active Profile: output:analog-stereo+input:analog-stereo",
+            "Active Profile: off",
+            1,
+        )
+    }
+
+    #[test]
+    fn unused_card_switched_off_is_ok() {
+        let result =
+            check_card_output_profile(&pactl(fixtures::DEFAULT_SINK, fixtures::LIST_CARDS));
+
+        assert_eq!(result.status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn bluetooth_default_with_an_unused_card_off_is_ok() {
+        let result =
+            check_card_output_profile(&pactl("bluez_output.AA_BB_CC_DD_EE_FF.1\n", &all_off()));
+
+        assert_eq!(result.status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn virtual_default_with_an_unused_card_off_is_ok() {
+        let result = check_card_output_profile(&pactl("easyeffects_sink\n", fixtures::LIST_CARDS));
+
+        assert_eq!(result.status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn every_card_off_is_an_error_with_the_best_profile() {
+        let result = check_card_output_profile(&pactl("", &all_off()));
+
+        assert_eq!(result.code.as_deref(), Some("card.profile_off"));
+        assert_eq!(
+            result.fix_command,
+            Some(vec![
+                "pactl".to_string(),
+                "set-card-profile".to_string(),
+                "alsa_card.pci-0000_00_1f.3".to_string(),
+                "output:analog-stereo+input:analog-stereo".to_string(),
+            ])
+        );
+    }
+}
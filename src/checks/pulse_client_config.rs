@@ -0,0 +1,115 @@
+//! Check 11: PulseAudio Client Config
+//! Detects client.conf overrides that stop clients from reaching the audio server.
+
+use std::fs;
+use std::path::PathBuf;
+
+use super::parsing::last_setting;
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+use crate::xdg;
+
+pub(super) const CHECK_NAME: &str = "pulse_client_config";
+
+/// Check whether a failed server connection is explained by the client configuration.
+pub fn check_pulse_client_config(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let pactl_output = runner.run("pactl", &["info"]);
+    if pactl_output.success {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "PulseAudio client config is not blocking connections",
+        );
+    }
+    debug_info.push_str(&format!("pactl info:\n{}\n", pactl_output.stderr.trim()));
+
+    let client_config = std::env::var("PULSE_CLIENTCONFIG").ok();
+    let config_path = std::env::var("PULSE_CONFIG_PATH").ok();
+    debug_info.push_str(&format!(
+        "PULSE_CLIENTCONFIG={}\nPULSE_CONFIG_PATH={}\n",
+        client_config.as_deref().unwrap_or("(unset)"),
+        config_path.as_deref().unwrap_or("(unset)")
+    ));
+
+    // The first candidate that exists is the one libpulse uses
+    let effective = config_candidates(client_config.as_deref(), config_path.as_deref())
+        .into_iter()
+        .find(|p| p.exists());
+
+    let autospawn = effective.as_ref().and_then(|path| {
+        let contents = fs::read_to_string(path).ok()?;
+        parse_autospawn(&contents)
+    });
+
+    debug_info.push_str(&format!(
+        "effective client.conf: {}\nautospawn: {}\n",
+        effective
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "(none)".to_string()),
+        autospawn.as_deref().unwrap_or("(default)")
+    ));
+
+    if let (Some(path), Some(value)) = (&effective, &autospawn) {
+        if value == "no" {
+            return CheckResult::warning(
+                CHECK_NAME,
+                format!(
+                    "Audio server is not running and autospawn is disabled (autospawn = no in {})",
+                    path.display()
+                ),
+                format!(
+                    "Start the server (systemctl --user start pipewire-pulse) or remove 'autospawn = no' from {}",
+                    path.display()
+                ),
+            )
+            .with_code("client.autospawn_disabled")
+            .with_debug(debug_info);
+        }
+    }
+
+    if let Some(ref path) = client_config {
+        return CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "Cannot reach the audio server while PULSE_CLIENTCONFIG points at {}",
+                path
+            ),
+            "Unset PULSE_CLIENTCONFIG and try again",
+        )
+        .with_code("client.config_override")
+        .with_debug(debug_info);
+    }
+
+    CheckResult::ok(CHECK_NAME, "No restrictive PulseAudio client config found")
+        .with_debug(debug_info)
+}
+
+/// Candidate client.conf locations in the order libpulse consults them.
+pub(super) fn config_candidates(
+    client_config: Option<&str>,
+    config_path: Option<&str>,
+) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+
+    if let Some(path) = client_config {
+        candidates.push(PathBuf::from(path));
+    }
+
+    if let Some(dir) = config_path {
+        candidates.push(PathBuf::from(dir).join("client.conf"));
+    }
+
+    if let Some(dir) = xdg::config_home() {
+        candidates.push(dir.join("pulse").join("client.conf"));
+    }
+
+    candidates.push(PathBuf::from("/etc/pulse/client.conf"));
+    candidates
+}
+
+/// Returns the last `autospawn = ...` value, ignoring `;` and `#` comments.
+fn parse_autospawn(contents: &str) -> Option<String> {
+    last_setting(contents, "autospawn").map(|value| value.to_lowercase())
+}
@@ -0,0 +1,68 @@
+//! Check 29: User Session Runtime Directory
+//! Detects a missing or foreign XDG_RUNTIME_DIR, which leaves the user's audio server unreachable.
+
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+
+use super::session_access::current_identity;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "runtime_dir";
+
+/// Check that `XDG_RUNTIME_DIR` is set, exists, and belongs to the current user.
+///
+/// The audio server's socket and the user bus live there, so without it every
+/// later check that talks to the server fails for no visible reason.
+pub fn check_runtime_dir() -> CheckResult {
+    let mut debug_info = String::new();
+
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
+        .ok()
+        .filter(|dir| !dir.is_empty());
+    let uid = current_identity().uid;
+    debug_info.push_str(&format!(
+        "XDG_RUNTIME_DIR={}\nuid: {}\n",
+        runtime_dir.as_deref().unwrap_or("(unset)"),
+        uid.map(|u| u.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    ));
+
+    let problem = match runtime_dir {
+        None => "XDG_RUNTIME_DIR is not set".to_string(),
+        Some(ref dir) => match fs::metadata(dir) {
+            Err(e) => format!("XDG_RUNTIME_DIR {} is unusable: {}", dir, e),
+            Ok(meta) if !meta.is_dir() => format!("XDG_RUNTIME_DIR {} is not a directory", dir),
+            Ok(meta) if uid.is_some_and(|u| u != meta.uid()) => format!(
+                "XDG_RUNTIME_DIR {} belongs to uid {}, not you",
+                dir,
+                meta.uid()
+            ),
+            Ok(_) => {
+                return CheckResult::ok(
+                    CHECK_NAME,
+                    format!("User session runtime directory is {}", dir),
+                )
+                .with_debug(debug_info)
+            }
+        },
+    };
+    debug_info.push_str(&format!("{}\n", problem));
+
+    let expected = uid
+        .map(|u| format!("/run/user/{}", u))
+        .unwrap_or_else(|| "/run/user/$(id -u)".to_string());
+
+    CheckResult::error(
+        CHECK_NAME,
+        format!(
+            "No user session bus; audio can't be reached ({}). Are you on SSH without a session?",
+            problem
+        ),
+        format!(
+            "Run from a desktop session, or log in so systemd starts your user session and run: export XDG_RUNTIME_DIR={}",
+            expected
+        ),
+    )
+    .with_code("session.no_runtime_dir")
+    .with_debug(debug_info)
+}
@@ -0,0 +1,188 @@
+//! Check 18: Sample Rate Support
+//! Detects a default sink running at a sample rate its formats don't list.
+
+use super::parsing::{field, find_block};
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "sample_rate";
+
+/// Check that the default sink's active sample rate is one it advertises.
+pub fn check_sample_rate(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let default_sink_output = runner.run("pactl", &["get-default-sink"]);
+    let default_sink = default_sink_output.stdout.trim();
+
+    if default_sink.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "No sample rate issues (no default sink)");
+    }
+
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
+    if !sinks_output.success {
+        return CheckResult::ok(CHECK_NAME, "No sample rate issues (cannot list sinks)")
+            .with_debug(debug_info);
+    }
+
+    let spec = match parse_sink_rates(&sinks_output.stdout, default_sink) {
+        Some(spec) => spec,
+        None => {
+            return CheckResult::ok(
+                CHECK_NAME,
+                "No sample rate issues (default sink not in sink list)",
+            )
+            .with_debug(debug_info)
+        }
+    };
+
+    debug_info.push_str(&format!(
+        "sample specification: {}\nformats:\n{}\nadvertised rates: {:?}\n",
+        spec.sample_spec,
+        spec.formats.join("\n"),
+        spec.supported_rates
+    ));
+
+    let rate = match spec.active_rate {
+        Some(rate) => rate,
+        None => {
+            return CheckResult::ok(CHECK_NAME, "No sample rate issues (rate not reported)")
+                .with_debug(debug_info)
+        }
+    };
+
+    // Most ALSA sinks only list "pcm" with no rate constraint
+    if spec.supported_rates.is_empty() || spec.supported_rates.contains(&rate) {
+        return CheckResult::ok(CHECK_NAME, format!("Default sink runs at {} Hz", rate))
+            .with_debug(debug_info);
+    }
+
+    CheckResult::warning(
+        CHECK_NAME,
+        format!(
+            "Default sink runs at {} Hz, which it doesn't list as supported ({})",
+            rate,
+            spec.supported_rates
+                .iter()
+                .map(|r| format!("{} Hz", r))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        "Set a supported rate in PipeWire (default.clock.allowed-rates) or PulseAudio (default-sample-rate)",
+    )
+    .with_code("rate.unsupported")
+    .with_debug(debug_info)
+}
+
+struct SinkRates {
+    sample_spec: String,
+    active_rate: Option<u32>,
+    formats: Vec<String>,
+    supported_rates: Vec<u32>,
+}
+
+/// Parse the `Sample Specification:` and `Formats:` entries of `target_sink`
+/// from `pactl list sinks` output.
+fn parse_sink_rates(output: &str, target_sink: &str) -> Option<SinkRates> {
+    let block = find_block(output, target_sink)?;
+    let mut spec = SinkRates {
+        sample_spec: String::new(),
+        active_rate: None,
+        formats: Vec::new(),
+        supported_rates: Vec::new(),
+    };
+    let mut in_formats = false;
+
+    for line in block.lines() {
+        let trimmed = line.trim();
+
+        if let Some(value) = field(line, "Sample Specification") {
+            spec.sample_spec = value.to_string();
+            // e.g. "s16le 2ch 44100Hz"
+            spec.active_rate = value
+                .split_whitespace()
+                .find_map(|part| part.strip_suffix("Hz"))
+                .and_then(|r| r.parse().ok());
+            in_formats = false;
+        } else if field(line, "Formats").is_some() {
+            in_formats = true;
+        } else if in_formats {
+            // Formats run to the end of the sink block, one per line
+            if trimmed.is_empty() || trimmed.ends_with(':') {
+                in_formats = false;
+                continue;
+            }
+            spec.formats.push(trimmed.to_string());
+            for rate in format_rates(trimmed) {
+                if !spec.supported_rates.contains(&rate) {
+                    spec.supported_rates.push(rate);
+                }
+            }
+        }
+    }
+
+    spec.supported_rates.sort_unstable();
+    Some(spec)
+}
+
+/// Rates from a format line such as `pcm, format.rate = "[ 44100, 48000 ]"`.
+fn format_rates(format: &str) -> Vec<u32> {
+    let value = match format.split_once("format.rate = ") {
+        Some((_, rest)) => rest,
+        None => return Vec::new(),
+    };
+
+    // The value is quoted and either a single rate or a bracketed list
+    let value = value.trim_start_matches('"');
+    let value = &value[..value.find('"').unwrap_or(value.len())];
+
+    value
+        .trim_matches(|c: char| c == '[' || c == ']' || c.is_whitespace())
+        .split(',')
+        .filter_map(|r| r.trim().parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::fixtures;
+    use crate::runner::MockRunner;
+    use crate::types::CheckStatus;
+
+    fn pactl(sinks: &str) -> MockRunner {
+        MockRunner::new()
+            .with("pactl get-default-sink", fixtures::DEFAULT_SINK)
+            .with("pactl list sinks", sinks)
+    }
+
+    #[test]
+    fn unconstrained_pcm_format_accepts_any_rate() {
+        let result = check_sample_rate(&pactl(fixtures::LIST_SINKS));
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert_eq!(result.message, "Default sink runs at 48000 Hz");
+    }
+
+    #[test]
+    fn unlisted_rate_is_a_warning() {
+        let sinks = fixtures::LIST_SINKS.replacen(
+            "\t\tpcm\n",
+            "\t\tpcm, format.rate = \"[ 44100, 96000 ]\"\n",
+            1,
+        );
+        let result = check_sample_rate(&pactl(&sinks));
+
+        assert_eq!(result.code.as_deref(), Some("rate.unsupported"));
+        assert!(result.message.contains("(44100 Hz, 96000 Hz)"));
+    }
+
+    #[test]
+    fn parses_single_and_listed_rates() {
+        assert_eq!(format_rates("pcm, format.rate = \"48000\""), vec![48000]);
+        assert_eq!(
+            format_rates("pcm, format.rate = \"[ 44100, 48000 ]\""),
+            vec![44100, 48000]
+        );
+        assert!(format_rates("pcm").is_empty());
+    }
+}
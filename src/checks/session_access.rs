@@ -0,0 +1,263 @@
+//! Check 7: Session Device Access
+//! Detects whether logind granted the current session access to the sound devices.
+
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::runner::run_command;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "session_access";
+
+/// Check that the current session can open the ALSA device nodes in `/dev/snd`.
+pub fn check_session_access() -> CheckResult {
+    let mut debug_info = String::new();
+
+    let session_id = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "self".to_string());
+    let session_output = run_command(
+        "loginctl",
+        &[
+            "show-session",
+            &session_id,
+            "-p",
+            "Seat",
+            "-p",
+            "Active",
+            "-p",
+            "Remote",
+        ],
+    );
+    debug_info.push_str(&format!(
+        "loginctl show-session {}:\n{}{}\n",
+        session_id, session_output.stdout, session_output.stderr
+    ));
+
+    if !session_output.success
+        && (session_output.stderr.contains("not found")
+            || session_output.stderr.contains("No such file"))
+    {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Cannot check session device access (loginctl not available)",
+        )
+        .with_debug(debug_info);
+    }
+
+    let session = parse_session(&session_output.stdout);
+
+    let devices = list_snd_devices();
+    if devices.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "No sound device nodes to check in /dev/snd")
+            .with_debug(debug_info);
+    }
+
+    let identity = current_identity();
+    debug_info.push_str(&format!(
+        "uid: {:?}, groups: {:?}\n",
+        identity.uid, identity.groups
+    ));
+
+    let mut denied: Vec<String> = Vec::new();
+    // Devices only an ACL could grant, whose ACL couldn't be read
+    let mut unknown: Vec<String> = Vec::new();
+
+    for device in &devices {
+        let access = device_access(device, &identity);
+        debug_info.push_str(&format!("{}: {}\n", device.display(), access.describe()));
+        if access.granted {
+            continue;
+        }
+        if access.acl_checked {
+            denied.push(device.display().to_string());
+        } else {
+            unknown.push(device.display().to_string());
+        }
+    }
+
+    if denied.is_empty() && !unknown.is_empty() {
+        return CheckResult::ok(
+            CHECK_NAME,
+            format!(
+                "Session device access not fully checked (cannot read the ACLs of {})",
+                unknown.join(", ")
+            ),
+        )
+        .with_debug(debug_info);
+    }
+
+    if denied.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "Session has access to the sound devices")
+            .with_debug(debug_info);
+    }
+
+    let seat = if session.seat.is_empty() {
+        "no seat".to_string()
+    } else {
+        format!("seat {}", session.seat)
+    };
+    let mut context = vec![seat];
+    if session.remote {
+        context.push("remote".to_string());
+    }
+    if !session.active {
+        context.push("inactive".to_string());
+    }
+
+    CheckResult::warning(
+        CHECK_NAME,
+        format!(
+            "Session ({}) has no access to {}",
+            context.join(", "),
+            denied.join(", ")
+        ),
+        "Log in on a local seat so logind grants device access (uaccess), or add your user to the 'audio' group",
+    )
+    .with_code("session.device_access_denied")
+    .with_debug(debug_info)
+}
+
+#[derive(Default)]
+struct SessionInfo {
+    seat: String,
+    active: bool,
+    remote: bool,
+}
+
+fn parse_session(output: &str) -> SessionInfo {
+    let mut session = SessionInfo::default();
+
+    for line in output.lines() {
+        if let Some((key, value)) = line.trim().split_once('=') {
+            match key {
+                "Seat" => session.seat = value.to_string(),
+                "Active" => session.active = value == "yes",
+                "Remote" => session.remote = value == "yes",
+                _ => {}
+            }
+        }
+    }
+
+    session
+}
+
+/// Control and PCM nodes are what applications actually open.
+fn list_snd_devices() -> Vec<PathBuf> {
+    let mut devices: Vec<PathBuf> = match fs::read_dir("/dev/snd") {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.starts_with("controlC") || n.starts_with("pcmC"))
+                    .unwrap_or(false)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    devices.sort();
+    devices
+}
+
+/// The process's effective uid and supplementary groups.
+pub(super) struct Identity {
+    pub(super) uid: Option<u32>,
+    pub(super) groups: Vec<u32>,
+}
+
+/// Read the current identity from `/proc/self/status`.
+pub(super) fn current_identity() -> Identity {
+    let status = fs::read_to_string("/proc/self/status").unwrap_or_default();
+    let mut identity = Identity {
+        uid: None,
+        groups: Vec::new(),
+    };
+
+    for line in status.lines() {
+        if let Some(rest) = line.strip_prefix("Uid:") {
+            // Real, effective, saved, filesystem: the effective uid is what counts
+            identity.uid = rest.split_whitespace().nth(1).and_then(|s| s.parse().ok());
+        } else if let Some(rest) = line.strip_prefix("Groups:") {
+            identity.groups = rest
+                .split_whitespace()
+                .filter_map(|s| s.parse().ok())
+                .collect();
+        }
+    }
+
+    identity
+}
+
+struct DeviceAccess {
+    granted: bool,
+    mode: u32,
+    owner: u32,
+    group: u32,
+    acl: Option<String>,
+    /// False when getfacl is missing or failed, so an ACL may still grant access.
+    acl_checked: bool,
+}
+
+impl DeviceAccess {
+    fn describe(&self) -> String {
+        format!(
+            "mode {:o}, owner {}, group {}, acl: {}, access: {}",
+            self.mode & 0o777,
+            self.owner,
+            self.group,
+            match (&self.acl, self.acl_checked) {
+                (Some(acl), _) => acl.as_str(),
+                (None, true) => "none",
+                (None, false) => "unknown",
+            },
+            if self.granted { "yes" } else { "no" }
+        )
+    }
+}
+
+fn device_access(path: &Path, identity: &Identity) -> DeviceAccess {
+    let (mode, owner, group) = match fs::metadata(path) {
+        Ok(meta) => (meta.mode(), meta.uid(), meta.gid()),
+        Err(_) => (0, 0, 0),
+    };
+
+    let by_owner = identity.uid == Some(owner) && mode & 0o600 == 0o600;
+    let by_group = identity.groups.contains(&group) && mode & 0o060 == 0o060;
+    let by_other = mode & 0o006 == 0o006;
+
+    let mut access = DeviceAccess {
+        granted: by_owner || by_group || by_other,
+        mode,
+        owner,
+        group,
+        acl: None,
+        acl_checked: false,
+    };
+
+    if access.granted {
+        return access;
+    }
+
+    // Fall back to the ACL entries logind adds for the active seat's user
+    if let Some(uid) = identity.uid {
+        let path_str = path.to_string_lossy();
+        let acl_output = run_command("getfacl", &["-n", "-p", &path_str]);
+        if acl_output.success {
+            access.acl_checked = true;
+            let user_entry = format!("user:{}:", uid);
+            access.acl = acl_output
+                .stdout
+                .lines()
+                .find(|l| l.starts_with(&user_entry))
+                .map(|l| l.to_string());
+            access.granted = access
+                .acl
+                .as_deref()
+                .map(|l| l[user_entry.len()..].starts_with("rw"))
+                .unwrap_or(false);
+        }
+    }
+
+    access
+}
@@ -1,33 +1,57 @@
 //! Check 5: Active Streams Misrouted
-//! Detects if apps are bound to a non-default sink.
+//! Detects if apps are bound to a non-default sink (or the default is stale), or muted / near-silent / paused on their own.
 
-use crate::runner::run_command;
-use crate::types::CheckResult;
+use super::mute_state::parse_channel_percentages;
+use super::sink_names_match;
+use super::sink_validity::parse_sink_info;
+use crate::runner::CommandRunner;
+use crate::types::{CheckResult, Subsystem};
 
-const CHECK_NAME: &str = "sink_inputs";
+pub(super) const CHECK_NAME: &str = "sink_inputs";
+
+/// Symbolic target that always means the current default sink.
+const DEFAULT_SINK_ALIAS: &str = "@DEFAULT_SINK@";
+
+/// An app stream below this volume (on every channel) is effectively silent.
+const APP_LOW_VOLUME_PERCENT: u32 = 5;
 
 /// Check if there are active stream inputs that might be misrouted.
-pub fn check_sink_inputs() -> CheckResult {
+/// Streams are expected on `target_sink` instead of the default sink when given (`--sink`).
+/// With `pid` (`--pid`), only that process's streams are looked at.
+pub fn check_sink_inputs(
+    runner: &dyn CommandRunner,
+    target_sink: Option<&str>,
+    pid: Option<u32>,
+) -> CheckResult {
     let mut debug_info = String::new();
 
     // Get default sink
-    let default_sink_output = run_command("pactl", &["get-default-sink"]);
-    let default_sink = default_sink_output.stdout.trim().to_string();
+    let default_sink = match target_sink {
+        Some(sink) => sink.to_string(),
+        None => runner
+            .run("pactl", &["get-default-sink"])
+            .stdout
+            .trim()
+            .to_string(),
+    };
 
     if default_sink.is_empty() {
         return CheckResult::warning(
             CHECK_NAME,
             "Cannot check stream routing (no default sink)",
             "Set a default output device first",
-        );
+        )
+        .with_code("routing.no_default_sink")
+        .with_subsystem(Subsystem::Server);
     }
 
     // Get sink inputs
-    let inputs_output = run_command("pactl", &["list", "sink-inputs"]);
+    let inputs_output = runner.run("pactl", &["list", "sink-inputs"]);
     debug_info.push_str(&format!(
         "pactl list sink-inputs:\n{}\n",
         inputs_output.stdout
     ));
+    debug_info.push_str(&inputs_output.utf8_note("pactl list sink-inputs"));
 
     if !inputs_output.success {
         return CheckResult::warning(
@@ -35,37 +59,145 @@ pub fn check_sink_inputs() -> CheckResult {
             "Cannot list active audio streams",
             "Ensure audio server is running",
         )
+        .with_code("routing.list_failed")
+        .with_subsystem(Subsystem::Server)
         .with_debug(debug_info);
     }
 
     // Parse sink inputs
-    let inputs = parse_sink_inputs(&inputs_output.stdout);
+    let mut inputs = parse_sink_inputs(&inputs_output.stdout);
+
+    if let Some(pid) = pid {
+        inputs.retain(|input| input.process_id == Some(pid));
+        debug_info.push_str(&format!("streams from PID {}: {}\n", pid, inputs.len()));
+
+        if inputs.is_empty() {
+            return CheckResult::warning(
+                CHECK_NAME,
+                format!("Process {} isn't producing audio (no output stream)", pid),
+                "The app may not have opened an output stream yet: start playback in it and run again. Streams and their PIDs are listed by 'pactl list sink-inputs'",
+            )
+            .with_code("routing.pid_no_stream")
+            .with_debug(debug_info);
+        }
+    }
 
     if inputs.is_empty() {
         return CheckResult::ok(CHECK_NAME, "No active audio streams (nothing playing)")
             .with_debug(debug_info);
     }
 
+    // An app can be muted or turned down while the sink itself is fine;
+    // routing problems are reported first, naming these alongside
+    let silenced: Vec<String> = inputs
+        .iter()
+        .filter_map(|input| {
+            let loudest = input.volumes.iter().max().copied();
+            if input.muted == Some(true) {
+                Some(format!("'{}' is muted", input.app_name))
+            } else {
+                loudest
+                    .filter(|&v| v < APP_LOW_VOLUME_PERCENT)
+                    .map(|v| format!("'{}' is at {}%", input.app_name, v))
+            }
+        })
+        .collect();
+
     // Get sink index to name mapping
-    let sinks_output = run_command("pactl", &["list", "sinks"]);
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
     let sink_map = parse_sink_index_map(&sinks_output.stdout);
 
+    // The default sink's index, for streams whose sink name doesn't match it
+    // because lossy decoding mangled the name differently in each listing
+    let default_index = sink_map
+        .iter()
+        .find(|(_, name)| sink_names_match(name, &default_sink))
+        .map(|(idx, _)| *idx);
+
     // Check for misrouted streams
     let mut misrouted: Vec<String> = Vec::new();
+    // Sinks that uncorked (actively playing) misrouted streams are on
+    let mut playing_elsewhere: Vec<String> = Vec::new();
 
     for input in &inputs {
-        // Resolve sink index to name if possible
-        let sink_name = sink_map
-            .iter()
-            .find(|(idx, _)| *idx == input.sink_index)
-            .map(|(_, name)| name.as_str())
-            .unwrap_or("");
+        let is_misrouted;
+        let sink_name;
 
-        if !sink_name.is_empty() && sink_name != default_sink {
+        match (input.sink_index, input.sink_name.as_deref()) {
+            (Some(index), _) => {
+                // Resolve sink index to name if possible
+                sink_name = sink_map
+                    .iter()
+                    .find(|(idx, _)| *idx == index)
+                    .map(|(_, name)| name.as_str())
+                    .unwrap_or("");
+
+                is_misrouted = !sink_name.is_empty()
+                    && !sink_names_match(sink_name, &default_sink)
+                    && default_index != Some(index);
+            }
+            // Some servers report the target by name instead of index
+            (None, Some(name)) => {
+                sink_name = name;
+                is_misrouted = name != DEFAULT_SINK_ALIAS && !sink_names_match(name, &default_sink);
+            }
+            (None, None) => {
+                debug_info.push_str(&format!(
+                    "routing unknown for '{}' (no sink index or name)\n",
+                    input.app_name
+                ));
+                continue;
+            }
+        }
+
+        if is_misrouted {
             misrouted.push(format!(
                 "'{}' is playing to '{}'",
                 input.app_name, sink_name
             ));
+            if input.corked == Some(false) && !playing_elsewhere.iter().any(|s| s == sink_name) {
+                playing_elsewhere.push(sink_name.to_string());
+            }
+        }
+    }
+
+    // Streams playing elsewhere while the configured default is gone or
+    // unplugged means the default is stale, not that the streams are wrong
+    if target_sink.is_none() && !playing_elsewhere.is_empty() {
+        let default_info = parse_sink_info(&sinks_output.stdout, &default_sink);
+        let default_unavailable = match default_info {
+            Some(ref info) => info.port_availability == "not available",
+            None => true,
+        };
+
+        if default_unavailable {
+            debug_info.push_str(&format!(
+                "default sink '{}' is {}; streams playing on: {}\n",
+                default_sink,
+                if default_info.is_some() {
+                    "unplugged"
+                } else {
+                    "missing"
+                },
+                playing_elsewhere.join(", ")
+            ));
+
+            return CheckResult::warning(
+                CHECK_NAME,
+                format!(
+                    "Default output '{}' is unavailable while streams play to '{}' (stale default){}",
+                    default_sink,
+                    playing_elsewhere[0],
+                    also_silenced(&silenced)
+                ),
+                format!(
+                    "Make the working output the default: pactl set-default-sink {}",
+                    playing_elsewhere[0]
+                ),
+            )
+            .with_code("routing.stale_default")
+            .with_fix(&["pactl", "set-default-sink", playing_elsewhere[0].as_str()])
+            .with_debug(debug_info);
         }
     }
 
@@ -73,52 +205,222 @@ pub fn check_sink_inputs() -> CheckResult {
         CheckResult::warning(
             CHECK_NAME,
             format!(
-                "{} stream(s) playing to non-default output: {}",
+                "{} stream(s) playing to non-default output: {}{}",
                 misrouted.len(),
-                misrouted.join(", ")
+                misrouted.join(", "),
+                also_silenced(&silenced)
             ),
             "Move streams to default output in sound settings or pavucontrol",
         )
+        .with_code("routing.misrouted")
         .with_debug(debug_info)
-    } else {
-        CheckResult::ok(
+    } else if !silenced.is_empty() {
+        CheckResult::warning(
             CHECK_NAME,
-            format!("{} active stream(s) correctly routed", inputs.len()),
+            format!(
+                "{} app stream(s) silenced: {}",
+                silenced.len(),
+                silenced.join(", ")
+            ),
+            "Unmute or raise the app's volume in its own controls or in pavucontrol (Playback tab)",
         )
+        .with_code("routing.app_silenced")
+        .with_subsystem(Subsystem::Volume)
         .with_debug(debug_info)
+    } else if let Some(pid) = pid {
+        let streams: Vec<String> = inputs
+            .iter()
+            .map(|input| describe_stream(input, &sink_map))
+            .collect();
+        CheckResult::ok(CHECK_NAME, format!("PID {}: {}", pid, streams.join("; ")))
+            .with_debug(debug_info)
+    } else {
+        // Paused streams are silent by design, which is easy to mistake for a fault
+        let corked: Vec<String> = inputs
+            .iter()
+            .filter(|input| input.corked == Some(true))
+            .map(|input| format!("'{}'", input.app_name))
+            .collect();
+        let message = if corked.is_empty() {
+            format!("{} active stream(s) correctly routed", inputs.len())
+        } else if corked.len() == inputs.len() {
+            format!(
+                "{} stream(s) correctly routed, but all are paused (corked), so nothing is actively playing",
+                inputs.len()
+            )
+        } else {
+            format!(
+                "{} active stream(s) correctly routed; paused (corked): {}",
+                inputs.len(),
+                corked.join(", ")
+            )
+        };
+        CheckResult::ok(CHECK_NAME, message).with_debug(debug_info)
     }
 }
 
-struct SinkInput {
-    app_name: String,
-    sink_index: u32,
+/// `"; also silenced: ..."` naming the muted or near-silent app streams, or
+/// an empty string if there are none.
+fn also_silenced(silenced: &[String]) -> String {
+    if silenced.is_empty() {
+        String::new()
+    } else {
+        format!("; also silenced: {}", silenced.join(", "))
+    }
 }
 
-fn parse_sink_inputs(output: &str) -> Vec<SinkInput> {
+/// One stream's sink, mute state, volume, and whether it's paused, e.g.
+/// `'Firefox' plays to 'alsa_output.pci-0000_00_1f.3.analog-stereo', unmuted at 65%`.
+fn describe_stream(input: &SinkInput, sink_map: &[(u32, String)]) -> String {
+    let sink = input
+        .sink_index
+        .and_then(|index| {
+            sink_map
+                .iter()
+                .find(|(idx, _)| *idx == index)
+                .map(|(_, name)| name.as_str())
+        })
+        .or(input.sink_name.as_deref())
+        .unwrap_or("unknown sink");
+    let mute = match input.muted {
+        Some(true) => "muted",
+        Some(false) => "unmuted",
+        None => "mute unknown",
+    };
+    let volume = input
+        .volumes
+        .iter()
+        .max()
+        .map(|v| format!(" at {}%", v))
+        .unwrap_or_default();
+    let paused = if input.corked == Some(true) {
+        " (paused)"
+    } else {
+        ""
+    };
+
+    format!(
+        "'{}' plays to '{}', {}{}{}",
+        input.app_name, sink, mute, volume, paused
+    )
+}
+
+pub(super) struct SinkInput {
+    pub(super) app_name: String,
+    /// Index of the target sink; `None` when `Sink:` holds a name instead.
+    pub(super) sink_index: Option<u32>,
+    /// Target sink name, when `Sink:` holds a name rather than an index.
+    pub(super) sink_name: Option<String>,
+    pub(super) process_id: Option<u32>,
+    pub(super) process_binary: String,
+    pub(super) muted: Option<bool>,
+    /// Whether the stream is paused (`Corked: yes`); uncorked streams are playing.
+    pub(super) corked: Option<bool>,
+    pub(super) volumes: Vec<u32>,
+}
+
+/// Parse `pactl list sink-inputs` into one entry per `Sink Input #N` block.
+///
+/// Only fields at the block's own indentation are read as `Sink:`, `Mute:`,
+/// `Corked:`, and `Volume:`; property lines are nested deeper, so a property
+/// value that happens to contain "Sink:" is never taken as the routing target.
+pub(super) fn parse_sink_inputs(output: &str) -> Vec<SinkInput> {
     let mut inputs = Vec::new();
+    let mut in_input = false;
+    let mut field_indent: Option<usize> = None;
     let mut current_sink_index: Option<u32> = None;
+    let mut current_sink_name: Option<String> = None;
     let mut current_app_name = String::new();
+    let mut current_process_id: Option<u32> = None;
+    let mut current_process_binary = String::new();
+    let mut current_muted: Option<bool> = None;
+    let mut current_corked: Option<bool> = None;
+    let mut current_volumes: Vec<u32> = Vec::new();
 
     for line in output.lines() {
         let trimmed = line.trim();
+        let indent = line.len() - line.trim_start().len();
 
-        if trimmed.starts_with("Sink:") {
+        if trimmed.starts_with("Sink Input #") {
             // Save previous input
-            if let Some(idx) = current_sink_index {
+            if in_input {
                 inputs.push(SinkInput {
                     app_name: if current_app_name.is_empty() {
                         "Unknown".to_string()
                     } else {
                         current_app_name.clone()
                     },
-                    sink_index: idx,
+                    sink_index: current_sink_index,
+                    sink_name: current_sink_name.clone(),
+                    process_id: current_process_id,
+                    process_binary: current_process_binary.clone(),
+                    muted: current_muted,
+                    corked: current_corked,
+                    volumes: current_volumes.clone(),
                 });
             }
 
-            // Parse new input
-            let sink_str = trimmed.strip_prefix("Sink:").unwrap_or("").trim();
-            current_sink_index = sink_str.parse().ok();
+            in_input = true;
+            field_indent = None;
+            current_sink_index = None;
+            current_sink_name = None;
             current_app_name.clear();
+            current_process_id = None;
+            current_process_binary.clear();
+            current_muted = None;
+            current_corked = None;
+            current_volumes.clear();
+            continue;
+        }
+
+        if !in_input || trimmed.is_empty() {
+            continue;
+        }
+
+        // The first line after the header sets the block-level indentation
+        let at_block_level = *field_indent.get_or_insert(indent) == indent;
+
+        if at_block_level {
+            if let Some(sink_str) = trimmed.strip_prefix("Sink:") {
+                let sink_str = sink_str.trim();
+                current_sink_index = sink_str.parse().ok();
+                current_sink_name = if current_sink_index.is_none() && !sink_str.is_empty() {
+                    Some(sink_str.to_string())
+                } else {
+                    None
+                };
+            }
+
+            if let Some(mute_value) = trimmed.strip_prefix("Mute:") {
+                current_muted = Some(mute_value.trim().eq_ignore_ascii_case("yes"));
+            }
+
+            if let Some(corked_value) = trimmed.strip_prefix("Corked:") {
+                current_corked = Some(corked_value.trim().eq_ignore_ascii_case("yes"));
+            }
+
+            if trimmed.starts_with("Volume:") {
+                current_volumes = parse_channel_percentages(trimmed);
+            }
+
+            continue;
+        }
+
+        if trimmed.starts_with("application.process.id = ") {
+            current_process_id = trimmed
+                .strip_prefix("application.process.id = ")
+                .unwrap_or("")
+                .trim_matches('"')
+                .parse()
+                .ok();
+        }
+
+        if trimmed.starts_with("application.process.binary = ") {
+            current_process_binary = trimmed
+                .strip_prefix("application.process.binary = ")
+                .unwrap_or("")
+                .trim_matches('"')
+                .to_string();
         }
 
         if trimmed.starts_with("application.name = ") {
@@ -140,14 +442,20 @@ fn parse_sink_inputs(output: &str) -> Vec<SinkInput> {
     }
 
     // Don't forget the last one
-    if let Some(idx) = current_sink_index {
+    if in_input {
         inputs.push(SinkInput {
             app_name: if current_app_name.is_empty() {
                 "Unknown".to_string()
             } else {
                 current_app_name
             },
-            sink_index: idx,
+            sink_index: current_sink_index,
+            sink_name: current_sink_name,
+            process_id: current_process_id,
+            process_binary: current_process_binary,
+            muted: current_muted,
+            corked: current_corked,
+            volumes: current_volumes,
         });
     }
 
@@ -176,3 +484,159 @@ fn parse_sink_index_map(output: &str) -> Vec<(u32, String)> {
 
     map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::fixtures;
+    use crate::runner::MockRunner;
+    use crate::types::CheckStatus;
+
+    fn pactl(inputs: &str) -> MockRunner {
+        MockRunner::new()
+            .with("pactl get-default-sink", fixtures::DEFAULT_SINK)
+            .with("pactl list sink-inputs", inputs)
+            .with("pactl list sinks", fixtures::LIST_SINKS)
+    }
+
+    /// Only the Firefox stream, on the default sink.
+    fn firefox_only() -> String {
+        fixtures::LIST_SINK_INPUTS
+            .split("\n\n")
+            .next()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn parses_app_sink_mute_and_volume() {
+        let inputs = parse_sink_inputs(fixtures::LIST_SINK_INPUTS);
+
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0].app_name, "Firefox");
+        assert_eq!(inputs[0].sink_index, Some(48));
+        assert_eq!(inputs[0].process_id, Some(4242));
+        assert_eq!(inputs[0].muted, Some(false));
+        assert_eq!(inputs[0].volumes, vec![100, 100]);
+        assert_eq!(inputs[1].app_name, "Spotify");
+        assert_eq!(inputs[1].volumes, vec![80, 80]);
+    }
+
+    #[test]
+    fn stream_on_the_default_sink_is_ok() {
+        let result = check_sink_inputs(&pactl(&firefox_only()), None, None);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert_eq!(result.message, "1 active stream(s) correctly routed");
+    }
+
+    #[test]
+    fn muted_app_is_silenced() {
+        let inputs = firefox_only().replace("Mute: no", "Mute: yes");
+        let result = check_sink_inputs(&pactl(&inputs), None, None);
+
+        assert_eq!(result.code.as_deref(), Some("routing.app_silenced"));
+        assert!(result.message.contains("'Firefox' is muted"));
+    }
+
+    #[test]
+    fn quiet_app_is_silenced() {
+        let inputs = firefox_only().replace("65536 / 100%", "1311 /   2%");
+        let result = check_sink_inputs(&pactl(&inputs), None, None);
+
+        assert_eq!(result.code.as_deref(), Some("routing.app_silenced"));
+        assert!(result.message.contains("'Firefox' is at 2%"));
+    }
+
+    #[test]
+    fn misrouting_is_reported_before_a_silenced_app() {
+        let inputs = fixtures::LIST_SINK_INPUTS.replacen("Mute: no", "Mute: yes", 1);
+        let result = check_sink_inputs(&pactl(&inputs), None, None);
+
+        assert_eq!(result.code.as_deref(), Some("routing.misrouted"));
+        assert!(result.message.contains("'Spotify' is playing to"));
+        assert!(result.message.contains("also silenced: 'Firefox' is muted"));
+    }
+
+    #[test]
+    fn stream_on_a_sink_with_a_mangled_name_is_not_misrouted() {
+        // The default sink's name lost its bytes differently in each listing
+        let runner = MockRunner::new()
+            .with("pactl get-default-sink", "usb-\u{FFFD}\u{FFFD}-headset\n")
+            .with("pactl list sink-inputs", &firefox_only())
+            .with(
+                "pactl list sinks",
+                &fixtures::LIST_SINKS.replace(
+                    "Name: alsa_output.pci-0000_00_1f.3.analog-stereo",
+                    "Name: usb-\u{FFFD}-headset",
+                ),
+            );
+        let result = check_sink_inputs(&runner, None, None);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn nested_lines_do_not_override_block_fields() {
+        let inputs = firefox_only().replacen(
+            "\tProperties:\n",
+            "\tProperties:\n\t\tSink: 52\n\t\tMute: yes\n\t\tVolume: front-left: 0 /   0% / -inf dB\n",
+            1,
+        );
+        let inputs = parse_sink_inputs(&inputs);
+
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].sink_index, Some(48));
+        assert_eq!(inputs[0].muted, Some(false));
+        assert_eq!(inputs[0].volumes, vec![100, 100]);
+    }
+
+    #[test]
+    fn parses_the_corked_flag() {
+        let inputs = parse_sink_inputs(fixtures::LIST_SINK_INPUTS);
+
+        assert_eq!(inputs[0].corked, Some(false));
+        assert_eq!(inputs[1].corked, Some(true));
+    }
+
+    #[test]
+    fn all_corked_streams_mean_nothing_is_playing() {
+        let inputs = firefox_only().replacen("Corked: no", "Corked: yes", 1);
+        let result = check_sink_inputs(&pactl(&inputs), None, None);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert!(result.message.contains("nothing is actively playing"));
+    }
+
+    #[test]
+    fn corked_stream_is_named_beside_playing_ones() {
+        let paused = firefox_only()
+            .replacen("Corked: no", "Corked: yes", 1)
+            .replace("Firefox", "Rhythmbox");
+        let inputs = format!("{}\n\n{}", firefox_only(), paused);
+        let result = check_sink_inputs(&pactl(&inputs), None, None);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert!(result.message.ends_with("paused (corked): 'Rhythmbox'"));
+    }
+
+    #[test]
+    fn sink_inside_property_values_is_not_a_routing_target() {
+        let inputs = firefox_only()
+            .replace(
+                "media.name = \"AudioStream\"",
+                "media.name = \"Sink test\"\n\t\tmedia.title = \"Sink Input #99\"",
+            )
+            .replacen(
+                "\tProperties:\n",
+                "\tProperties:\n\t\tmedia.comment = \"routed by\n\t\t  Sink: 52\"\n",
+                1,
+            );
+        let inputs = parse_sink_inputs(&inputs);
+
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].app_name, "Firefox");
+        assert_eq!(inputs[0].sink_index, Some(48));
+        assert_eq!(inputs[0].sink_name, None);
+    }
+}
@@ -1,50 +1,87 @@
 //! Check 3: Default Sink Validity
-//! Detects if the default sink exists, is not suspended, and is not a disconnected HDMI.
+//! Detects if the default sink exists, is not suspended, and its output port is plugged in.
 
-use crate::runner::run_command;
-use crate::types::CheckResult;
+use std::fs;
+use std::path::PathBuf;
 
-const CHECK_NAME: &str = "default_sink";
+use super::card_parse::parse_cards;
+use super::parsing::property;
+use super::sink_names_match;
+use super::wpctl;
+use crate::runner::{audio_cli, AudioCli, CommandRunner};
+use crate::types::{CheckResult, Subsystem};
+
+pub(super) const CHECK_NAME: &str = "default_sink";
+
+/// A `module-suspend-on-idle` timeout at or below this suspends the sink
+/// between sounds, which some hardware never recovers from.
+const AGGRESSIVE_SUSPEND_TIMEOUT_SECS: u32 = 1;
 
 /// Check if the default sink is valid and usable.
-pub fn check_default_sink() -> CheckResult {
+/// Checks `target_sink` instead of the default sink when given (`--sink`).
+/// `last_sink` is the default that passed on the previous run, if known.
+pub fn check_default_sink(
+    runner: &dyn CommandRunner,
+    target_sink: Option<&str>,
+    last_sink: Option<&str>,
+) -> CheckResult {
+    if audio_cli(runner) == AudioCli::Wpctl {
+        return check_default_sink_wpctl(runner);
+    }
+
     let mut debug_info = String::new();
 
     // Get default sink name
-    let default_sink_output = run_command("pactl", &["get-default-sink"]);
-    debug_info.push_str(&format!(
-        "pactl get-default-sink:\n{}\n",
-        default_sink_output.stdout.trim()
-    ));
+    let default_sink_output;
+    let default_sink = match target_sink {
+        Some(sink) => {
+            debug_info.push_str(&format!("target sink (--sink): {}\n", sink));
+            sink
+        }
+        None => {
+            default_sink_output = runner.run("pactl", &["get-default-sink"]);
+            debug_info.push_str(&format!(
+                "pactl get-default-sink:\n{}\n",
+                default_sink_output.stdout.trim()
+            ));
+            debug_info.push_str(&default_sink_output.utf8_note("pactl get-default-sink"));
 
-    if !default_sink_output.success {
-        return CheckResult::error(
-            CHECK_NAME,
-            "Cannot determine default sink (audio server not responding)",
-            "Ensure PipeWire or PulseAudio is running",
-        )
-        .with_debug(debug_info);
-    }
+            if !default_sink_output.success {
+                return CheckResult::error(
+                    CHECK_NAME,
+                    "Cannot determine default sink (audio server not responding)",
+                    "Ensure PipeWire or PulseAudio is running",
+                )
+                .with_code("sink.server_unreachable")
+                .with_subsystem(Subsystem::Server)
+                .with_debug(debug_info);
+            }
 
-    let default_sink = default_sink_output.stdout.trim();
-    if default_sink.is_empty() {
-        return CheckResult::error(
-            CHECK_NAME,
-            "No default sink configured",
-            "Set a default output device in your sound settings",
-        )
-        .with_debug(debug_info);
-    }
+            let default_sink = default_sink_output.stdout.trim();
+            if default_sink.is_empty() {
+                return CheckResult::error(
+                    CHECK_NAME,
+                    "No default sink configured",
+                    "Set a default output device in your sound settings",
+                )
+                .with_code("sink.no_default")
+                .with_debug(debug_info);
+            }
+            default_sink
+        }
+    };
 
     // Get sink details
-    let sinks_output = run_command("pactl", &["list", "sinks"]);
+    let sinks_output = runner.run("pactl", &["list", "sinks"]);
     debug_info.push_str(&format!(
         "pactl list sinks (truncated):\n{}\n",
         sinks_output.stdout.chars().take(2000).collect::<String>()
     ));
+    debug_info.push_str(&sinks_output.utf8_note("pactl list sinks"));
 
     if !sinks_output.success {
         return CheckResult::warning(CHECK_NAME, "Cannot list sinks", "Check audio server status")
+            .with_code("sink.list_failed")
             .with_debug(debug_info);
     }
 
@@ -52,113 +89,431 @@ pub fn check_default_sink() -> CheckResult {
     let sink_info = parse_sink_info(&sinks_output.stdout, default_sink);
 
     match sink_info {
+        None if is_unplugged_usb(runner, default_sink, &mut debug_info) => CheckResult::error(
+            CHECK_NAME,
+            format!("Default output device was unplugged ('{}')", default_sink),
+            "Plug the USB device back in, or pick a device that is present in your sound settings",
+        )
+        .with_code("sink.device_unplugged")
+        .with_debug(debug_info),
         None => CheckResult::error(
             CHECK_NAME,
             format!("Default sink '{}' not found in sink list", default_sink),
             "Your default audio device may have been removed. Select a new output device.",
         )
+        .with_code("sink.default_missing")
         .with_debug(debug_info),
         Some(info) => {
-            // Check for SUSPENDED state
+            if is_dummy_output(&info.name, &info.description) {
+                return dummy_output_result(debug_info);
+            }
+
+            // Jack sensing marks the active port unavailable when nothing is plugged in
+            let port_unplugged = info.active_port.to_lowercase().contains("unavailable")
+                || info.port_availability == "not available";
+
+            let is_hdmi = info.name.to_lowercase().contains("hdmi")
+                || info.description.to_lowercase().contains("hdmi");
+
+            if port_unplugged {
+                // Resume can flip the default back to HDMI behind the user's back
+                let previous = last_sink.filter(|last| {
+                    is_hdmi
+                        && target_sink.is_none()
+                        && !sink_names_match(last, &info.name)
+                        && parse_sink_info(&sinks_output.stdout, last).is_some()
+                });
+                if let Some(previous) = previous {
+                    debug_info.push_str(&format!("last good default sink: {}\n", previous));
+                    return CheckResult::error(
+                        CHECK_NAME,
+                        format!(
+                            "Default output switched to disconnected HDMI ({}) since the last run (was '{}')",
+                            info.description, previous
+                        ),
+                        format!(
+                            "Switch back to the previous output: pactl set-default-sink {}",
+                            previous
+                        ),
+                    )
+                    .with_code("sink.hdmi_default_switched")
+                    .with_fix(&["pactl", "set-default-sink", previous])
+                    .with_debug(debug_info);
+                }
+
+                if is_hdmi {
+                    return CheckResult::error(
+                        CHECK_NAME,
+                        format!(
+                            "Default output is HDMI ({}) but appears disconnected",
+                            info.description
+                        ),
+                        "Switch output to Built-in Audio or connect your HDMI display",
+                    )
+                    .with_code("sink.hdmi_unplugged")
+                    .with_debug(debug_info);
+                }
+
+                return CheckResult::error(
+                    CHECK_NAME,
+                    format!(
+                        "Default output port '{}' on {} appears unplugged",
+                        info.active_port, info.description
+                    ),
+                    "Plug in your headphones or speakers, or switch to another output",
+                )
+                .with_code("sink.port_unplugged")
+                .with_debug(debug_info);
+            }
+
+            // An idle sink is suspended; only worth a note once its port is known to be plugged in
             if info.state.to_uppercase() == "SUSPENDED" {
+                let modules_output = runner.run("pactl", &["list", "modules", "short"]);
+                let suspend_module = find_suspend_on_idle(&modules_output.stdout);
+                if let Some(ref args) = suspend_module {
+                    debug_info.push_str(&format!("module-suspend-on-idle args: {}\n", args));
+                }
+
+                if let Some(timeout) = suspend_module.as_deref().map(suspend_timeout) {
+                    if timeout <= AGGRESSIVE_SUSPEND_TIMEOUT_SECS {
+                        return CheckResult::warning(
+                            CHECK_NAME,
+                            format!(
+                                "Default sink is SUSPENDED and module-suspend-on-idle has a {}s timeout",
+                                timeout
+                            ),
+                            "The sink may fail to wake up. Unload the module (pactl unload-module module-suspend-on-idle) or raise its timeout in /etc/pulse/default.pa",
+                        )
+                        .with_code("sink.suspend_on_idle_aggressive")
+                        .with_fix(&["pactl", "unload-module", "module-suspend-on-idle"])
+                        .with_debug(debug_info);
+                    }
+                }
+
                 return CheckResult::warning(
                     CHECK_NAME,
                     "Default sink is SUSPENDED (no active audio streams)",
                     "This is normal when nothing is playing. Try playing audio.",
                 )
+                .with_code("sink.suspended")
                 .with_debug(debug_info);
             }
 
-            // Check for HDMI that might be disconnected
-            let is_hdmi = info.name.to_lowercase().contains("hdmi")
-                || info.description.to_lowercase().contains("hdmi");
-
-            if is_hdmi {
-                // Check if there's an active port or if it's unplugged
-                if info.active_port.to_lowercase().contains("unavailable")
-                    || info.port_availability == "not available"
+            // A display can carry video while telling the GPU it has no audio;
+            // the ELD files only describe this machine
+            if is_hdmi && runner.is_live() {
+                if let Some(monitor) =
+                    hdmi_monitor_without_audio(info.card.as_deref(), &mut debug_info)
                 {
                     return CheckResult::error(
                         CHECK_NAME,
                         format!(
-                            "Default output is HDMI ({}) but appears disconnected",
-                            info.description
+                            "Default output is HDMI ({}) but the display ({}) doesn't expose audio",
+                            info.description, monitor
                         ),
-                        "Switch output to Built-in Audio or connect your HDMI display",
+                        "Use the display's audio input or speakers, check its audio settings, or switch output to Built-in Audio",
                     )
+                    .with_code("sink.hdmi_no_audio")
                     .with_debug(debug_info);
                 }
             }
 
-            // Sink is valid
-            CheckResult::ok(CHECK_NAME, format!("Default sink: {}", info.description))
-                .with_debug(debug_info)
+            debug_info.push_str(&format!(
+                "channel map: {} ({} channel(s)), profile: {}\n",
+                info.channel_map.join(","),
+                info.channel_map.len(),
+                info.profile.as_deref().unwrap_or("unknown")
+            ));
+
+            // Sink is valid; an odd channel layout is worth a note, not a failure
+            match channel_map_note(&info) {
+                Some(note) => CheckResult::ok(
+                    CHECK_NAME,
+                    format!("Default sink: {} (note: {})", info.description, note),
+                ),
+                None => CheckResult::ok(CHECK_NAME, format!("Default sink: {}", info.description)),
+            }
+            .with_debug(debug_info)
+        }
+    }
+}
+
+/// Check the default sink through `wpctl` when `pactl` is not installed.
+fn check_default_sink_wpctl(runner: &dyn CommandRunner) -> CheckResult {
+    let status_output = runner.run("wpctl", &["status"]);
+    let debug_info = format!(
+        "wpctl status (truncated):\n{}\n",
+        status_output.stdout.chars().take(2000).collect::<String>()
+    );
+
+    if !status_output.success {
+        return CheckResult::error(
+            CHECK_NAME,
+            "Cannot determine default sink (audio server not responding)",
+            "Ensure PipeWire and WirePlumber are running",
+        )
+        .with_code("sink.server_unreachable")
+        .with_subsystem(Subsystem::Server)
+        .with_debug(debug_info);
+    }
+
+    let sinks = wpctl::parse_status_sinks(&status_output.stdout);
+
+    match sinks.iter().find(|s| s.is_default) {
+        None => CheckResult::error(
+            CHECK_NAME,
+            "No default sink configured",
+            "Set a default output device in your sound settings",
+        )
+        .with_code("sink.no_default")
+        .with_debug(debug_info),
+        Some(sink) if is_dummy_output("", &sink.description) => dummy_output_result(debug_info),
+        Some(sink) => CheckResult::ok(
+            CHECK_NAME,
+            format!("Default sink: {} (node {})", sink.description, sink.id),
+        )
+        .with_debug(debug_info),
+    }
+}
+
+/// Look through the HDMI ELD files of `card` (or every card) for a connected
+/// display whose ELD is invalid or lists no audio formats. Returns the
+/// display's name when found.
+fn hdmi_monitor_without_audio(card: Option<&str>, debug_info: &mut String) -> Option<String> {
+    let card_dirs: Vec<PathBuf> = match card {
+        Some(card) => vec![PathBuf::from(format!("/proc/asound/card{}", card))],
+        None => fs::read_dir("/proc/asound")
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| {
+                        p.file_name()
+                            .map(|n| n.to_string_lossy().starts_with("card"))
+                            .unwrap_or(false)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    let mut connected = 0;
+    let mut without_audio: Option<String> = None;
+
+    for dir in card_dirs {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            if !entry.file_name().to_string_lossy().starts_with("eld#") {
+                continue;
+            }
+            let eld = fs::read_to_string(entry.path()).unwrap_or_default();
+            let field = |key: &str| {
+                eld.lines().find_map(|l| {
+                    let (k, v) = l.split_once(char::is_whitespace)?;
+                    (k == key).then(|| v.trim().to_string())
+                })
+            };
+
+            if field("monitor_present").as_deref() != Some("1") {
+                continue;
+            }
+            connected += 1;
+
+            let eld_valid = field("eld_valid").as_deref() == Some("1");
+            let rates: Vec<&str> = eld
+                .lines()
+                .filter(|l| l.starts_with("sad") && l.contains("_rates"))
+                .collect();
+            let monitor = field("monitor_name").unwrap_or_else(|| "unknown display".to_string());
+
+            debug_info.push_str(&format!(
+                "{}: monitor_present 1, eld_valid {}, monitor_name {}, rates: {}\n",
+                entry.path().display(),
+                if eld_valid { 1 } else { 0 },
+                monitor,
+                if rates.is_empty() {
+                    "none".to_string()
+                } else {
+                    rates.join("; ")
+                }
+            ));
+
+            if eld_valid && !rates.is_empty() {
+                // One display with audio is enough for the sink to work
+                return None;
+            }
+            without_audio.get_or_insert(monitor);
         }
     }
+
+    if connected == 0 {
+        return None;
+    }
+    without_audio
 }
 
-struct SinkInfo {
-    name: String,
-    description: String,
-    state: String,
-    active_port: String,
-    port_availability: String,
+/// True when `sink` names a USB device that no card backs anymore: the
+/// server still remembers it as the default after it was unplugged.
+fn is_unplugged_usb(runner: &dyn CommandRunner, sink: &str, debug_info: &mut String) -> bool {
+    if !sink.contains(".usb-") {
+        return false;
+    }
+
+    let cards_output = runner.run("pactl", &["list", "cards"]);
+    if !cards_output.success {
+        return false;
+    }
+
+    let backed = parse_cards(&cards_output.stdout)
+        .iter()
+        .any(|card| card.backs_sink(sink));
+    debug_info.push_str(&format!(
+        "USB card backing '{}': {}\n",
+        sink,
+        if backed { "present" } else { "absent" }
+    ));
+
+    !backed
 }
 
-fn parse_sink_info(output: &str, target_sink: &str) -> Option<SinkInfo> {
+/// The audio server falls back to a null sink when it finds no usable hardware.
+pub(super) fn is_dummy_output(name: &str, description: &str) -> bool {
+    name == "auto_null" || description.contains("Dummy Output")
+}
+
+fn dummy_output_result(debug_info: String) -> CheckResult {
+    CheckResult::error(
+        CHECK_NAME,
+        "Default output is the Dummy Output (no real sound device available)",
+        "The audio server found no usable hardware. \
+         Check that your sound card is detected and its driver is loaded.",
+    )
+    .with_code("sink.dummy_output")
+    .with_subsystem(Subsystem::Hardware)
+    .with_debug(debug_info)
+}
+
+/// Returns the arguments of a loaded `module-suspend-on-idle` from
+/// `pactl list modules short` output (`ID<TAB>NAME<TAB>ARGS`).
+fn find_suspend_on_idle(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut fields = line.split('\t');
+        let _id = fields.next()?;
+        if fields.next()? != "module-suspend-on-idle" {
+            return None;
+        }
+        Some(fields.next().unwrap_or("").trim().to_string())
+    })
+}
+
+/// The module's `timeout=` argument in seconds; PulseAudio defaults to 5.
+fn suspend_timeout(args: &str) -> u32 {
+    args.split_whitespace()
+        .find_map(|arg| arg.strip_prefix("timeout="))
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5)
+}
+
+pub(super) struct SinkInfo {
+    pub(super) name: String,
+    pub(super) description: String,
+    pub(super) state: String,
+    pub(super) active_port: String,
+    pub(super) port_availability: String,
+    /// ALSA card number from the `alsa.card` property, if present.
+    pub(super) card: Option<String>,
+    /// Channel positions from `Channel Map:`, e.g. `front-left`, `front-right`.
+    pub(super) channel_map: Vec<String>,
+    /// Card profile from the `device.profile.name` property, if present.
+    pub(super) profile: Option<String>,
+}
+
+/// Parse the block for `target_sink` out of `pactl list sinks` output.
+/// Also works on `pactl list sources`, which uses the same layout.
+pub(super) fn parse_sink_info(output: &str, target_sink: &str) -> Option<SinkInfo> {
     let mut current_name = String::new();
     let mut current_desc = String::new();
     let mut current_state = String::new();
     let mut current_active_port = String::new();
-    let mut current_port_availability = String::new();
+    // (port name, availability) for each entry under `Ports:`
+    let mut current_ports: Vec<(String, String)> = Vec::new();
+    let mut current_card: Option<String> = None;
+    let mut current_channel_map: Vec<String> = Vec::new();
+    let mut current_profile: Option<String> = None;
     let mut in_target_sink = false;
-    let mut in_ports_section = false;
+    let mut ports_indent: Option<usize> = None;
 
     for line in output.lines() {
         let trimmed = line.trim();
+        let indent = line.len() - line.trim_start().len();
 
-        // Detect sink boundary
-        if trimmed.starts_with("Name:") {
+        // Port entries are indented under `Ports:`; anything else ends the section
+        if ports_indent.is_some_and(|i| indent <= i) {
+            ports_indent = None;
+        }
+
+        // Detect sink boundary; `State:` comes before `Name:` in each block
+        if trimmed.starts_with("Sink #") || trimmed.starts_with("Source #") {
             // Save previous sink if it was the target
             if in_target_sink {
                 return Some(SinkInfo {
                     name: current_name,
                     description: current_desc,
                     state: current_state,
+                    port_availability: port_availability(&current_ports, &current_active_port),
                     active_port: current_active_port,
-                    port_availability: current_port_availability,
+                    card: current_card,
+                    channel_map: current_channel_map,
+                    profile: current_profile,
                 });
             }
 
-            current_name = trimmed
-                .strip_prefix("Name:")
-                .unwrap_or("")
-                .trim()
-                .to_string();
-            in_target_sink = current_name == target_sink;
+            in_target_sink = false;
+            current_name.clear();
             current_desc.clear();
             current_state.clear();
             current_active_port.clear();
-            current_port_availability.clear();
-            in_ports_section = false;
+            current_ports.clear();
+            current_card = None;
+            current_channel_map.clear();
+            current_profile = None;
+            ports_indent = None;
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix("Name:") {
+            current_name = name.trim().to_string();
+            in_target_sink = sink_names_match(&current_name, target_sink);
+        } else if let Some(state) = trimmed.strip_prefix("State:") {
+            current_state = state.trim().to_string();
         }
 
         if !in_target_sink {
             continue;
         }
 
-        if trimmed.starts_with("Description:") {
+        if ports_indent.is_some() {
+            // e.g. "analog-output-speaker: Speakers (type: Speaker, priority: 10000, not available)"
+            if let Some((port_name, rest)) = trimmed.split_once(": ") {
+                let availability = if rest.contains("not available") {
+                    "not available"
+                } else if rest.contains("available") {
+                    "available"
+                } else {
+                    ""
+                };
+                current_ports.push((port_name.to_string(), availability.to_string()));
+            }
+        } else if trimmed.starts_with("Description:") {
             current_desc = trimmed
                 .strip_prefix("Description:")
                 .unwrap_or("")
                 .trim()
                 .to_string();
-        } else if trimmed.starts_with("State:") {
-            current_state = trimmed
-                .strip_prefix("State:")
-                .unwrap_or("")
-                .trim()
-                .to_string();
         } else if trimmed.starts_with("Active Port:") {
             current_active_port = trimmed
                 .strip_prefix("Active Port:")
@@ -166,14 +521,17 @@ fn parse_sink_info(output: &str, target_sink: &str) -> Option<SinkInfo> {
                 .trim()
                 .to_string();
         } else if trimmed.starts_with("Ports:") {
-            in_ports_section = true;
-        } else if in_ports_section && trimmed.contains(&current_active_port) {
-            // Look for availability in the port line
-            if trimmed.contains("not available") {
-                current_port_availability = "not available".to_string();
-            } else if trimmed.contains("available") {
-                current_port_availability = "available".to_string();
-            }
+            ports_indent = Some(indent);
+        } else if let Some(map) = trimmed.strip_prefix("Channel Map:") {
+            current_channel_map = map
+                .split(',')
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty())
+                .collect();
+        } else if let Some(card) = property(line, "alsa.card") {
+            current_card = Some(card.to_string());
+        } else if let Some(profile) = property(line, "device.profile.name") {
+            current_profile = Some(profile.to_string());
         }
     }
 
@@ -183,10 +541,180 @@ fn parse_sink_info(output: &str, target_sink: &str) -> Option<SinkInfo> {
             name: current_name,
             description: current_desc,
             state: current_state,
+            port_availability: port_availability(&current_ports, &current_active_port),
             active_port: current_active_port,
-            port_availability: current_port_availability,
+            card: current_card,
+            channel_map: current_channel_map,
+            profile: current_profile,
         });
     }
 
     None
 }
+
+/// A short note when the sink's channel layout may explain sound from only
+/// some speakers: mono on stereo hardware, or a position mapped twice.
+fn channel_map_note(info: &SinkInfo) -> Option<String> {
+    let stereo_hardware = info
+        .profile
+        .as_deref()
+        .unwrap_or(&info.name)
+        .contains("stereo");
+
+    if info.channel_map.len() == 1 && stereo_hardware {
+        return Some("mono output on stereo hardware, only one speaker may play".to_string());
+    }
+
+    let duplicate = info
+        .channel_map
+        .iter()
+        .enumerate()
+        .find(|(i, c)| info.channel_map[..*i].contains(c));
+    duplicate.map(|(_, c)| format!("channel '{}' is mapped more than once", c))
+}
+
+/// Availability of the port whose name is exactly the active port's name.
+/// `Ports:` comes before `Active Port:`, so this runs once the block is read.
+fn port_availability(ports: &[(String, String)], active_port: &str) -> String {
+    let active_name = active_port.split_whitespace().next().unwrap_or("");
+    ports
+        .iter()
+        .find(|(name, _)| name == active_name)
+        .map(|(_, availability)| availability.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checks::fixtures;
+    use crate::runner::MockRunner;
+    use crate::types::CheckStatus;
+
+    const HDMI_SINK: &str = "alsa_output.pci-0000_00_1f.3.hdmi-stereo";
+
+    #[test]
+    fn working_default_sink_is_ok() {
+        let runner = MockRunner::new()
+            .with("pactl get-default-sink", fixtures::DEFAULT_SINK)
+            .with("pactl list sinks", fixtures::LIST_SINKS);
+        let result = check_default_sink(&runner, None, None);
+
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert_eq!(result.message, "Default sink: Built-in Audio Analog Stereo");
+    }
+
+    #[test]
+    fn unplugged_hdmi_default_is_an_error() {
+        let runner = MockRunner::new()
+            .with("pactl get-default-sink", HDMI_SINK)
+            .with("pactl list sinks", fixtures::LIST_SINKS);
+        let result = check_default_sink(&runner, None, None);
+
+        assert_eq!(result.status, CheckStatus::Error);
+        assert_eq!(result.code.as_deref(), Some("sink.hdmi_unplugged"));
+    }
+
+    #[test]
+    fn switch_to_hdmi_since_last_run_offers_the_previous_sink() {
+        let runner = MockRunner::new()
+            .with("pactl get-default-sink", HDMI_SINK)
+            .with("pactl list sinks", fixtures::LIST_SINKS);
+        let previous = fixtures::DEFAULT_SINK.trim();
+        let result = check_default_sink(&runner, None, Some(previous));
+
+        assert_eq!(result.code.as_deref(), Some("sink.hdmi_default_switched"));
+        assert_eq!(
+            result
+                .fix_command
+                .as_ref()
+                .and_then(|fix| fix.last())
+                .map(String::as_str),
+            Some(previous)
+        );
+    }
+
+    #[test]
+    fn missing_default_sink_is_an_error() {
+        let runner = MockRunner::new()
+            .with("pactl get-default-sink", "alsa_output.gone\n")
+            .with("pactl list sinks", fixtures::LIST_SINKS);
+        let result = check_default_sink(&runner, None, None);
+
+        assert_eq!(result.code.as_deref(), Some("sink.default_missing"));
+    }
+
+    #[test]
+    fn dummy_output_is_an_error() {
+        let runner = MockRunner::new()
+            .with("pactl get-default-sink", "auto_null\n")
+            .with(
+                "pactl list sinks",
+                "Sink #0\n\tState: IDLE\n\tName: auto_null\n\tDescription: Dummy Output\n",
+            );
+        let result = check_default_sink(&runner, None, None);
+
+        assert_eq!(result.code.as_deref(), Some("sink.dummy_output"));
+    }
+
+    #[test]
+    fn sink_names_with_replacement_characters_match() {
+        let sinks = "Sink #3\n\tState: RUNNING\n\tName: alsa_output.usb-Odd\u{FFFD}\u{FFFD}DAC\n\tDescription: Odd DAC\n";
+        assert!(parse_sink_info(sinks, "alsa_output.usb-Odd\u{FFFD}DAC").is_some());
+    }
+
+    #[test]
+    fn unplugged_analog_port_is_an_error() {
+        let sinks = fixtures::LIST_SINKS.replacen(
+            "Active Port: analog-output-speaker",
+            "Active Port: analog-output-headphones",
+            1,
+        );
+        let runner = MockRunner::new()
+            .with("pactl get-default-sink", fixtures::DEFAULT_SINK)
+            .with("pactl list sinks", &sinks);
+        let result = check_default_sink(&runner, None, None);
+
+        assert_eq!(result.status, CheckStatus::Error);
+        assert_eq!(result.code.as_deref(), Some("sink.port_unplugged"));
+        assert!(result.message.contains("'analog-output-headphones'"));
+    }
+
+    #[test]
+    fn port_availability_comes_from_the_active_port_only() {
+        let info = parse_sink_info(fixtures::LIST_SINKS, fixtures::DEFAULT_SINK.trim()).unwrap();
+        assert_eq!(info.active_port, "analog-output-speaker");
+        // The unplugged headphones port must not leak into the speaker's availability
+        assert_eq!(info.port_availability, "");
+
+        let info = parse_sink_info(fixtures::LIST_SINKS, HDMI_SINK).unwrap();
+        assert_eq!(info.port_availability, "not available");
+    }
+
+    #[test]
+    fn port_names_match_exactly() {
+        let sinks = fixtures::LIST_SINKS.replacen(
+            "\t\tanalog-output-speaker: Speakers (type: Speaker, priority: 10000, availability group: Legacy 3, availability unknown)\n",
+            "\t\tanalog-output-speaker-2: Speakers (type: Speaker, priority: 9000, not available)\n\t\tanalog-output-speaker: Speakers (type: Speaker, priority: 10000, available)\n",
+            1,
+        );
+        let info = parse_sink_info(&sinks, fixtures::DEFAULT_SINK.trim()).unwrap();
+
+        assert_eq!(info.port_availability, "available");
+    }
+
+    #[test]
+    fn stale_usb_default_is_reported_as_unplugged() {
+        let runner = MockRunner::new()
+            .with(
+                "pactl get-default-sink",
+                "alsa_output.usb-Focusrite_Scarlett_2i2-00.analog-stereo\n",
+            )
+            .with("pactl list sinks", fixtures::LIST_SINKS)
+            .with("pactl list cards", fixtures::LIST_CARDS);
+        let result = check_default_sink(&runner, None, None);
+
+        assert_eq!(result.status, CheckStatus::Error);
+        assert_eq!(result.code.as_deref(), Some("sink.device_unplugged"));
+    }
+}
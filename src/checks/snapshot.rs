@@ -0,0 +1,66 @@
+//! Shared capture of the audio server state for one diagnostic run.
+
+use std::sync::{Mutex, OnceLock};
+
+use crate::runner::{CommandOutput, CommandRunner};
+
+/// Caches the `pactl` listings several checks read, so each command runs at
+/// most once per `run_all_checks`. Each is captured the first time a check
+/// asks for it; any other command is passed straight to `inner`. Whether a
+/// program is installed is asked of `inner` once per program.
+pub(super) struct SystemSnapshot<'a> {
+    inner: &'a dyn CommandRunner,
+    default_sink: OnceLock<CommandOutput>,
+    sinks: OnceLock<CommandOutput>,
+    sink_inputs: OnceLock<CommandOutput>,
+    cards: OnceLock<CommandOutput>,
+    default_source: OnceLock<CommandOutput>,
+    sources: OnceLock<CommandOutput>,
+    installed: Mutex<Vec<(String, bool)>>,
+}
+
+impl<'a> SystemSnapshot<'a> {
+    pub(super) fn new(inner: &'a dyn CommandRunner) -> Self {
+        Self {
+            inner,
+            default_sink: OnceLock::new(),
+            sinks: OnceLock::new(),
+            sink_inputs: OnceLock::new(),
+            cards: OnceLock::new(),
+            default_source: OnceLock::new(),
+            sources: OnceLock::new(),
+            installed: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl CommandRunner for SystemSnapshot<'_> {
+    fn run(&self, program: &str, args: &[&str]) -> CommandOutput {
+        let cached = match (program, args) {
+            ("pactl", ["get-default-sink"]) => &self.default_sink,
+            ("pactl", ["list", "sinks"]) => &self.sinks,
+            ("pactl", ["list", "sink-inputs"]) => &self.sink_inputs,
+            ("pactl", ["list", "cards"]) => &self.cards,
+            ("pactl", ["get-default-source"]) => &self.default_source,
+            ("pactl", ["list", "sources"]) => &self.sources,
+            _ => return self.inner.run(program, args),
+        };
+
+        cached.get_or_init(|| self.inner.run(program, args)).clone()
+    }
+
+    fn is_installed(&self, program: &str) -> bool {
+        let mut installed = self.installed.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, known)) = installed.iter().find(|(name, _)| name == program) {
+            return *known;
+        }
+
+        let known = self.inner.is_installed(program);
+        installed.push((program.to_string(), known));
+        known
+    }
+
+    fn is_live(&self) -> bool {
+        self.inner.is_live()
+    }
+}
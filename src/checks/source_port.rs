@@ -0,0 +1,102 @@
+//! Check 19: Default Source Port
+//! Detects a microphone whose input port is unplugged or whose volume is too low to pick up signal.
+
+use super::mute_state::parse_mute_and_volume;
+use super::parsing::find_block;
+use super::sink_validity::parse_sink_info;
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "source_port";
+
+/// Below this input volume a microphone records what sounds like silence.
+const SOURCE_LOW_VOLUME_PERCENT: u32 = 10;
+
+/// Check that the default source's active port is plugged in and its volume can pass signal.
+pub fn check_source_port(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let default_source_output = runner.run("pactl", &["get-default-source"]);
+    let default_source = default_source_output.stdout.trim();
+
+    // Monitors have no port; missing defaults are reported by the default_source check
+    if default_source.is_empty() || default_source.ends_with(".monitor") {
+        return CheckResult::ok(CHECK_NAME, "No input port issues (no microphone selected)");
+    }
+
+    let sources_output = runner.run("pactl", &["list", "sources"]);
+    if !sources_output.success {
+        return CheckResult::ok(CHECK_NAME, "No input port issues (cannot list sources)");
+    }
+
+    debug_info.push_str(&format!(
+        "pactl list sources (default source):\n{}\n",
+        find_block(&sources_output.stdout, default_source).unwrap_or("")
+    ));
+
+    let info = match parse_sink_info(&sources_output.stdout, default_source) {
+        Some(info) => info,
+        None => {
+            return CheckResult::ok(
+                CHECK_NAME,
+                "No input port issues (default source not in source list)",
+            )
+            .with_debug(debug_info)
+        }
+    };
+
+    if info.port_availability == "not available" {
+        return CheckResult::warning(
+            CHECK_NAME,
+            format!(
+                "Microphone port '{}' on {} appears unplugged",
+                info.active_port, info.description
+            ),
+            "Plug in your microphone, or switch the input to another port (e.g. Internal Microphone) in sound settings",
+        )
+        .with_code("source.port_unplugged")
+        .with_debug(debug_info);
+    }
+
+    let (_, channel_volumes) = parse_mute_and_volume(&sources_output.stdout, default_source);
+    // 0% is already reported by the default_source check
+    if let Some(vol) = channel_volumes.iter().max().copied() {
+        if vol > 0 && vol < SOURCE_LOW_VOLUME_PERCENT {
+            return CheckResult::warning(
+                CHECK_NAME,
+                format!(
+                    "Microphone volume is very low ({}%, {})",
+                    vol, info.description
+                ),
+                format!(
+                    "Raise the input volume: pactl set-source-volume {} 100%",
+                    default_source
+                ),
+            )
+            .with_code("source.volume_low")
+            .with_fix(&["pactl", "set-source-volume", default_source, "100%"])
+            .with_debug(debug_info);
+        }
+    }
+
+    // Sources suspend when nothing is recording, which is normal
+    let state = if info.state.eq_ignore_ascii_case("SUSPENDED") {
+        ", suspended until an app records"
+    } else {
+        ""
+    };
+
+    CheckResult::ok(
+        CHECK_NAME,
+        format!(
+            "Microphone port '{}' is usable{}",
+            if info.active_port.is_empty() {
+                info.description.as_str()
+            } else {
+                info.active_port.as_str()
+            },
+            state
+        ),
+    )
+    .with_debug(debug_info)
+}
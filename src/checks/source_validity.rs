@@ -0,0 +1,136 @@
+//! Check 12: Default Source (Microphone)
+//! Detects a missing, muted, or silent default input device.
+
+use super::mute_state::parse_mute_and_volume;
+use super::sink_validity::parse_sink_info;
+use crate::runner::CommandRunner;
+use crate::types::{CheckResult, Subsystem};
+
+pub(super) const CHECK_NAME: &str = "default_source";
+
+/// Check if the default source (microphone) is present, unmuted, and audible.
+pub fn check_default_source(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let default_source_output = runner.run("pactl", &["get-default-source"]);
+    debug_info.push_str(&format!(
+        "pactl get-default-source:\n{}\n",
+        default_source_output.stdout.trim()
+    ));
+
+    if !default_source_output.success {
+        return CheckResult::warning(
+            CHECK_NAME,
+            "Cannot determine default input (audio server not responding)",
+            "Ensure PipeWire or PulseAudio is running",
+        )
+        .with_code("source.server_unreachable")
+        .with_subsystem(Subsystem::Server)
+        .with_debug(debug_info);
+    }
+
+    let default_source = default_source_output.stdout.trim();
+    if default_source.is_empty() {
+        return CheckResult::warning(
+            CHECK_NAME,
+            "No default input configured",
+            "Select a microphone in your sound settings",
+        )
+        .with_code("source.no_default")
+        .with_debug(debug_info);
+    }
+
+    // Monitor sources record what is playing, not a microphone
+    if default_source.ends_with(".monitor") {
+        return CheckResult::ok(
+            CHECK_NAME,
+            format!(
+                "Default input is a monitor of an output ({})",
+                default_source
+            ),
+        )
+        .with_debug(debug_info);
+    }
+
+    let sources_output = runner.run("pactl", &["list", "sources"]);
+    debug_info.push_str(&format!(
+        "pactl list sources (mute info):\n{}\n",
+        sources_output
+            .stdout
+            .lines()
+            .filter(|l| {
+                l.contains("Name:")
+                    || l.contains("State:")
+                    || l.contains("Mute:")
+                    || l.contains("Volume:")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    ));
+
+    if !sources_output.success {
+        return CheckResult::warning(
+            CHECK_NAME,
+            "Cannot list input devices",
+            "Check audio server status",
+        )
+        .with_code("source.list_failed")
+        .with_debug(debug_info);
+    }
+
+    let info = match parse_sink_info(&sources_output.stdout, default_source) {
+        Some(info) => info,
+        None => {
+            return CheckResult::warning(
+                CHECK_NAME,
+                format!(
+                    "Default input '{}' not found in source list",
+                    default_source
+                ),
+                "Your microphone may have been removed. Select a new input device.",
+            )
+            .with_code("source.default_missing")
+            .with_debug(debug_info)
+        }
+    };
+
+    let (is_muted, channel_volumes) = parse_mute_and_volume(&sources_output.stdout, default_source);
+
+    if is_muted == Some(true) {
+        return CheckResult::error(
+            CHECK_NAME,
+            format!("Microphone is muted ({})", info.description),
+            format!(
+                "Unmute it in sound settings or run: pactl set-source-mute {} 0",
+                default_source
+            ),
+        )
+        .with_code("source.muted")
+        .with_fix(&["pactl", "set-source-mute", default_source, "0"])
+        .with_debug(debug_info);
+    }
+
+    if !channel_volumes.is_empty() && channel_volumes.iter().all(|&v| v == 0) {
+        return CheckResult::error(
+            CHECK_NAME,
+            format!("Microphone volume is 0% ({})", info.description),
+            format!(
+                "Raise the input volume: pactl set-source-volume {} 100%",
+                default_source
+            ),
+        )
+        .with_code("source.volume_zero")
+        .with_fix(&["pactl", "set-source-volume", default_source, "100%"])
+        .with_debug(debug_info);
+    }
+
+    match channel_volumes.iter().max() {
+        Some(vol) => CheckResult::ok(
+            CHECK_NAME,
+            format!("Default input: {} (volume: {}%)", info.description, vol),
+        )
+        .with_debug(debug_info),
+        None => CheckResult::ok(CHECK_NAME, format!("Default input: {}", info.description))
+            .with_debug(debug_info),
+    }
+}
@@ -0,0 +1,87 @@
+//! Parsers for `wpctl` output, used when `pactl` is not installed.
+
+/// A sink as listed in the Audio section of `wpctl status`.
+pub(super) struct WpctlSink {
+    pub(super) id: u32,
+    pub(super) description: String,
+    pub(super) is_default: bool,
+}
+
+/// Parse the sinks out of the Audio section of `wpctl status`, e.g.:
+///
+/// ```text
+/// Audio
+///  ├─ Sinks:
+///  │  *   52. Built-in Audio Analog Stereo        [vol: 0.40]
+///  │      60. HDMI / DisplayPort                  [vol: 1.00 MUTED]
+/// ```
+pub(super) fn parse_status_sinks(output: &str) -> Vec<WpctlSink> {
+    let mut sinks = Vec::new();
+    let mut in_audio = false;
+    let mut in_sinks = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        // Top-level sections (Audio, Video, Settings) are not indented
+        if !line.starts_with(' ') && !trimmed.is_empty() {
+            in_audio = trimmed == "Audio";
+            in_sinks = false;
+            continue;
+        }
+
+        if !in_audio {
+            continue;
+        }
+
+        // Strip the tree-drawing prefix
+        let entry = trimmed.trim_start_matches(['│', '├', '└', '─']).trim();
+
+        if entry.ends_with(':') {
+            in_sinks = entry == "Sinks:";
+            continue;
+        }
+
+        if !in_sinks || entry.is_empty() {
+            continue;
+        }
+
+        let is_default = entry.starts_with('*');
+        let entry = entry.trim_start_matches('*').trim();
+
+        if let Some((id, rest)) = entry.split_once(". ") {
+            if let Ok(id) = id.trim().parse() {
+                // Drop the trailing "[vol: ...]" annotation
+                let description = match rest.rfind('[') {
+                    Some(pos) => rest[..pos].trim(),
+                    None => rest.trim(),
+                };
+                sinks.push(WpctlSink {
+                    id,
+                    description: description.to_string(),
+                    is_default,
+                });
+            }
+        }
+    }
+
+    sinks
+}
+
+/// Parse `wpctl get-volume` output like `Volume: 0.40 [MUTED]` into the
+/// mute flag and the volume as a percentage.
+pub(super) fn parse_get_volume(output: &str) -> (Option<bool>, Option<u32>) {
+    let line = match output.lines().find(|l| l.trim().starts_with("Volume:")) {
+        Some(line) => line.trim(),
+        None => return (None, None),
+    };
+
+    let muted = line.contains("[MUTED]");
+    let volume = line
+        .strip_prefix("Volume:")
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|v| v.parse::<f64>().ok())
+        .map(|v| (v * 100.0).round() as u32);
+
+    (Some(muted), volume)
+}
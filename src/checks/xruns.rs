@@ -0,0 +1,109 @@
+//! Check 25: Xruns
+//! Detects buffer under/overruns piling up on the default output, heard as crackling or dropouts.
+
+use super::sink_names_match;
+use crate::runner::CommandRunner;
+use crate::types::CheckResult;
+
+pub(super) const CHECK_NAME: &str = "xruns";
+
+/// A handful of xruns while devices start or reconfigure is normal; more
+/// than this on one node means they keep happening.
+const XRUN_WARNING_COUNT: u64 = 10;
+
+/// One node row from `pw-top -b` output.
+struct NodeErrors {
+    name: String,
+    errors: u64,
+}
+
+/// Check the default sink's xrun count in PipeWire's profiler.
+pub fn check_xruns(runner: &dyn CommandRunner) -> CheckResult {
+    let mut debug_info = String::new();
+
+    let output = runner.run("pw-top", &["-b", "-n", "1"]);
+    if output.stderr.contains("not found") || output.stderr.contains("No such file") {
+        return CheckResult::ok(CHECK_NAME, "Xruns not checked (pw-top not installed)")
+            .with_debug(format!("pw-top:\n{}\n", output.stderr));
+    }
+
+    if !output.success {
+        return CheckResult::ok(CHECK_NAME, "Xruns not checked (PipeWire not running)")
+            .with_debug(format!("pw-top:\n{}\n", output.stderr));
+    }
+
+    let nodes = parse_pw_top(&output.stdout);
+    debug_info.push_str("xruns per node (pw-top ERR):\n");
+    for node in &nodes {
+        debug_info.push_str(&format!("  {}: {}\n", node.name, node.errors));
+    }
+
+    let default_sink_output = runner.run("pactl", &["get-default-sink"]);
+    let default_sink = default_sink_output.stdout.trim();
+    debug_info.push_str(&format!("default sink: {}\n", default_sink));
+
+    if !default_sink_output.success || default_sink.is_empty() {
+        return CheckResult::ok(CHECK_NAME, "Xruns not checked (no default sink)")
+            .with_debug(debug_info);
+    }
+
+    match nodes
+        .iter()
+        .find(|node| sink_names_match(&node.name, default_sink))
+    {
+        None => CheckResult::ok(
+            CHECK_NAME,
+            "Xruns not checked (default sink not in the PipeWire graph)",
+        )
+        .with_debug(debug_info),
+        Some(node) if node.errors > XRUN_WARNING_COUNT => CheckResult::warning(
+            CHECK_NAME,
+            format!("Default output has had {} xruns (buffer under/overruns)", node.errors),
+            "Audio is crackling or dropping out because PipeWire misses its deadlines. Close CPU-heavy apps, or raise the quantum: pw-metadata -n settings 0 clock.force-quantum 1024",
+        )
+        .with_code("xruns.accumulating")
+        .with_debug(debug_info),
+        Some(node) => CheckResult::ok(
+            CHECK_NAME,
+            format!("Default output has {} xrun(s)", node.errors),
+        )
+        .with_debug(debug_info),
+    }
+}
+
+/// Read the name and ERR count of each node from `pw-top -b` output:
+///
+/// ```text
+/// S   ID  QUANT   RATE    WAIT    BUSY   W/Q   B/Q  ERR FORMAT           NAME
+/// R   30   1024  48000  38.2us  12.1us  0.00  0.00    3    S16LE 2 48000 alsa_output.pci-0000_00_1f.3.analog-stereo
+/// R   52   1024  48000  10.0us   4.5us  0.00  0.00    0    F32LE 2 48000  + Firefox
+/// ```
+///
+/// Columns before NAME are single tokens (`---` when unknown), so ERR is
+/// found by its position in the header. The name is the last token.
+fn parse_pw_top(output: &str) -> Vec<NodeErrors> {
+    let mut err_column = None;
+    let mut nodes = Vec::new();
+
+    for line in output.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        // Node rows also start with S (suspended), so match the header by NAME
+        if tokens.last() == Some(&"NAME") {
+            err_column = tokens.iter().position(|t| *t == "ERR");
+            continue;
+        }
+
+        let errors = err_column
+            .and_then(|column| tokens.get(column))
+            .and_then(|t| t.parse().ok());
+        if let (Some(errors), Some(name)) = (errors, tokens.last()) {
+            nodes.push(NodeErrors {
+                name: name.to_string(),
+                errors,
+            });
+        }
+    }
+
+    nodes
+}
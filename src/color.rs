@@ -0,0 +1,39 @@
+//! Minimal ANSI color support for human output.
+
+use std::io::IsTerminal;
+
+use crate::types::CheckStatus;
+
+pub const RED: &str = "\x1b[31m";
+pub const GREEN: &str = "\x1b[32m";
+pub const YELLOW: &str = "\x1b[33m";
+pub const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Decide whether to emit color: stdout must be a TTY, `--no-color` must be
+/// absent, and `NO_COLOR` must be unset or empty (see https://no-color.org).
+pub fn should_color(no_color_flag: bool) -> bool {
+    let no_color_env = std::env::var_os("NO_COLOR")
+        .map(|v| !v.is_empty())
+        .unwrap_or(false);
+
+    !no_color_flag && !no_color_env && std::io::stdout().is_terminal()
+}
+
+/// Wrap `text` in the given color code, or return it unchanged when disabled.
+pub fn paint(text: &str, color: &str, enabled: bool) -> String {
+    if enabled {
+        format!("{}{}{}", color, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// The color associated with a check status.
+pub fn for_status(status: CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Ok => GREEN,
+        CheckStatus::Warning => YELLOW,
+        CheckStatus::Error => RED,
+    }
+}
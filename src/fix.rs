@@ -0,0 +1,168 @@
+//! Interactive `--fix` mode: offers to run the fix commands attached to failing checks.
+
+use std::io::{self, BufRead, Write};
+
+use why_no_sound::checks::{self, CheckConfig, CheckFilter};
+use why_no_sound::runner::CommandRunner;
+use why_no_sound::types::{CheckResult, CheckStatus};
+
+/// Prompt for each failing check that carries a `fix_command`, run the ones the
+/// user confirms, and re-run those checks.
+///
+/// Returns `checks` with the re-run results swapped in.
+pub fn offer_fixes(checks: &[CheckResult], config: &CheckConfig) -> Vec<CheckResult> {
+    let fixable: Vec<(&CheckResult, &Vec<String>)> = checks
+        .iter()
+        .filter(|c| c.status != CheckStatus::Ok)
+        .filter_map(|c| c.fix_command.as_ref().map(|cmd| (c, cmd)))
+        .collect();
+
+    if fixable.is_empty() {
+        println!("\nNo automatic fixes available.");
+        return checks.to_vec();
+    }
+
+    println!("\n🔧 FIXES");
+    let runner = config.system_runner();
+    let mut applied: Vec<String> = Vec::new();
+
+    for (check, command) in fixable {
+        println!("\n{}", check.message);
+        println!("   $ {}", command.join(" "));
+
+        if !confirm(risk(command)) {
+            println!("   Skipped.");
+            continue;
+        }
+
+        let args: Vec<&str> = command[1..].iter().map(|s| s.as_str()).collect();
+        let output = runner.run(&command[0], &args);
+        if output.success {
+            println!("   Done.");
+            applied.push(check.name.clone());
+        } else {
+            println!("   Failed: {}", output.stderr.trim());
+        }
+    }
+
+    if applied.is_empty() {
+        return checks.to_vec();
+    }
+
+    let rerun = checks::run_all_checks(config, &CheckFilter::Only(applied));
+
+    println!();
+    for result in &rerun {
+        if result.status == CheckStatus::Ok {
+            println!("✅ {}: fixed", result.name);
+        } else {
+            println!(
+                "{} {}: still failing ({})",
+                result.status.emoji(),
+                result.name,
+                result.message
+            );
+        }
+    }
+
+    checks
+        .iter()
+        .map(|c| rerun.iter().find(|r| r.name == c.name).unwrap_or(c).clone())
+        .collect()
+}
+
+/// Above this, a volume fix may make whatever plays next uncomfortably loud.
+const SAFE_VOLUME_PERCENT: f64 = 50.0;
+
+/// Why `command` needs an explicit confirmation, if it does: it needs root,
+/// changes persistent system state, or sets a loud volume.
+fn risk(command: &[String]) -> Option<&'static str> {
+    let program = command.first().map(|s| s.as_str()).unwrap_or("");
+    let args: Vec<&str> = command.iter().skip(1).map(|s| s.as_str()).collect();
+
+    if program == "sudo"
+        || program == "modprobe"
+        || (program == "systemctl" && args.iter().any(|a| *a == "mask" || *a == "disable"))
+    {
+        return Some("This changes system configuration");
+    }
+
+    let sets_volume = match program {
+        "pactl" => args
+            .first()
+            .is_some_and(|a| *a == "set-sink-volume" || *a == "set-source-volume"),
+        "wpctl" => args.first() == Some(&"set-volume"),
+        "amixer" => args.contains(&"sset") || args.contains(&"set"),
+        _ => false,
+    };
+    let loud = args.last().is_some_and(|target| is_loud_volume(target));
+    (sets_volume && loud).then_some("This can make the output loud")
+}
+
+/// Returns true if a volume argument (`100%`, `+10%`, `1.0`) may end up
+/// above `SAFE_VOLUME_PERCENT`. A relative raise can't be judged, so it counts.
+fn is_loud_volume(target: &str) -> bool {
+    if target.starts_with('+') || target.ends_with('+') {
+        return true;
+    }
+
+    match target.strip_suffix('%') {
+        Some(percent) => percent
+            .parse::<f64>()
+            .is_ok_and(|p| p > SAFE_VOLUME_PERCENT),
+        // wpctl takes a plain factor, 1.0 being 100%
+        None => target
+            .parse::<f64>()
+            .is_ok_and(|factor| factor * 100.0 > SAFE_VOLUME_PERCENT),
+    }
+}
+
+/// Ask on stdin. Risky commands need a typed "yes" rather than "y", so they can
+/// never be confirmed by a blanket auto-confirm.
+fn confirm(risk: Option<&str>) -> bool {
+    let risky = risk.is_some();
+    if let Some(reason) = risk {
+        print!("   {}. Type 'yes' to run it: ", reason);
+    } else {
+        print!("   Run it? [y/N] ");
+    }
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    let answer = answer.trim().to_lowercase();
+    if risky {
+        answer == "yes"
+    } else {
+        answer == "y" || answer == "yes"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(line: &str) -> Vec<String> {
+        line.split(' ').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn root_and_persistent_changes_are_risky() {
+        assert!(risk(&command("sudo modprobe snd_hda_intel")).is_some());
+        assert!(risk(&command("systemctl --user mask pulseaudio")).is_some());
+        assert!(risk(&command("systemctl --user restart wireplumber")).is_none());
+    }
+
+    #[test]
+    fn loud_volume_changes_are_risky() {
+        assert!(risk(&command("pactl set-sink-volume @DEFAULT_SINK@ 100%")).is_some());
+        assert!(risk(&command("pactl set-sink-volume @DEFAULT_SINK@ +10%")).is_some());
+        assert!(risk(&command("wpctl set-volume @DEFAULT_AUDIO_SINK@ 1.0")).is_some());
+        assert!(risk(&command("amixer -c 0 sset PCM 100%")).is_some());
+        assert!(risk(&command("pactl set-sink-volume @DEFAULT_SINK@ 50%")).is_none());
+        assert!(risk(&command("pactl set-sink-mute @DEFAULT_SINK@ 0")).is_none());
+    }
+}
@@ -0,0 +1,30 @@
+//! why-no-sound: diagnose why Linux audio isn't working.
+//!
+//! The checks behind the `why-no-sound` binary, for programs that want to
+//! run the diagnosis themselves instead of shelling out to the CLI:
+//!
+//! ```no_run
+//! use why_no_sound::checks::{CheckConfig, CheckFilter};
+//! use why_no_sound::{build_report, run_all_checks, CheckStatus};
+//!
+//! let results = run_all_checks(&CheckConfig::default(), &CheckFilter::All);
+//! let report = build_report(results);
+//!
+//! if report.worst_status() != CheckStatus::Ok {
+//!     println!("{}", report.summary);
+//! }
+//! ```
+
+pub mod checks;
+pub mod color;
+pub mod output;
+pub mod pw_dump;
+pub mod report;
+pub mod runner;
+pub mod state;
+pub mod types;
+pub mod xdg;
+
+pub use checks::run_all_checks;
+pub use report::build_report;
+pub use types::{CheckResult, CheckStatus, DiagnosticReport};
@@ -1,12 +1,24 @@
 //! why-no-sound: A diagnostic CLI tool for Linux audio.
 
-mod checks;
-mod output;
-mod report;
-mod runner;
-mod types;
+mod fix;
+mod probe;
+mod progress;
+mod serve;
+mod signals;
+mod watch;
 
-use clap::Parser;
+use clap::error::ErrorKind;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{CommandFactory, Parser, ValueEnum};
+
+use why_no_sound::runner::CommandRunner;
+use why_no_sound::types::CheckStatus;
+use why_no_sound::{checks, color, output, pw_dump, report, runner, state, types};
 
 #[derive(Parser, Debug)]
 #[command(name = "why-no-sound")]
@@ -16,25 +28,544 @@ struct Args {
     #[arg(long)]
     json: bool,
 
+    /// Output results as single-line JSON (implies --json)
+    #[arg(long)]
+    json_compact: bool,
+
+    /// Omit timings and mask run-specific numbers so output diffs cleanly between runs
+    #[arg(long)]
+    stable: bool,
+
+    /// Redact Bluetooth MAC addresses, your user name, and the host name (for posting publicly)
+    #[arg(long)]
+    anonymize: bool,
+
+    /// Output results as Markdown (for bug reports and forums)
+    #[arg(long, conflicts_with_all = ["json", "json_compact"])]
+    markdown: bool,
+
+    /// Print a single summary line (status, probable cause, counts) and nothing else
+    #[arg(long, conflicts_with_all = ["json", "json_compact", "markdown", "summary_only", "fix", "watch"])]
+    oneline: bool,
+
+    /// List the available checks and exit without running them
+    #[arg(long, conflicts_with_all = ["markdown", "fix", "watch"])]
+    list_checks: bool,
+
+    /// Explain in plain language what a check looks at and how to fix it, then exit
+    #[arg(long, value_name = "CHECK", conflicts_with_all = ["json", "json_compact", "markdown", "fix", "watch", "list_checks"])]
+    explain: Option<String>,
+
+    /// Show which external programs are installed and which checks use them, then exit
+    #[arg(long, conflicts_with_all = ["json", "json_compact", "markdown", "fix", "watch", "list_checks"])]
+    check_deps: bool,
+
     /// Include debug info
     #[arg(long)]
     debug: bool,
+
+    /// More detail: -v is --debug, -vv also logs every command run to stderr
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Run only these checks (comma-separated check names)
+    #[arg(
+        long,
+        value_name = "NAMES",
+        value_delimiter = ',',
+        conflicts_with = "skip"
+    )]
+    only: Option<Vec<String>>,
+
+    /// Skip these checks (comma-separated check names)
+    #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+    skip: Option<Vec<String>>,
+
+    /// Comma-separated process names of apps that may bypass PipeWire via ALSA
+    #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+    direct_alsa_apps: Option<Vec<String>>,
+
+    /// Diagnose this sink (as in `pactl list short sinks`) instead of the default one
+    #[arg(long, value_name = "NAME")]
+    sink: Option<String>,
+
+    /// Look only at this process's audio streams: their sink, mute, and volume
+    #[arg(long, value_name = "PID")]
+    pid: Option<u32>,
+
+    /// Analyze saved command output in DIR (e.g. list-sinks.txt from 'pactl list sinks')
+    /// instead of this system; checks that need the live system are skipped
+    #[arg(
+        long,
+        value_name = "DIR",
+        value_parser = parse_directory,
+        conflicts_with_all = ["fix", "probe", "watch", "serve", "pw_dump"]
+    )]
+    from_dump: Option<PathBuf>,
+
+    /// Run this executable instead of `pactl` from PATH
+    #[arg(long, value_name = "PATH", value_parser = parse_executable)]
+    pactl_path: Option<PathBuf>,
+
+    /// Run this executable instead of `aplay` from PATH
+    #[arg(long, value_name = "PATH", value_parser = parse_executable)]
+    aplay_path: Option<PathBuf>,
+
+    /// Only scan kernel log entries newer than this (e.g. 30s, 10m, 1h, 2d); default: current boot
+    #[arg(long, value_name = "DURATION", value_parser = parse_log_since)]
+    log_since: Option<Duration>,
+
+    /// Attach the full `pw-dump` PipeWire graph to the report
+    #[arg(long)]
+    pw_dump: bool,
+
+    /// Maximum size in bytes of each debug section before it is truncated
+    #[arg(long, value_name = "BYTES", default_value_t = 65536)]
+    max_debug_bytes: usize,
+
+    /// Print only the diagnosis, probable cause, and suggested fixes
+    #[arg(long)]
+    summary_only: bool,
+
+    /// Disable ANSI colors (also honored: NO_COLOR environment variable)
+    #[arg(long)]
+    no_color: bool,
+
+    /// Use [OK]/[WARN]/[FAIL] markers instead of emoji (for screen readers)
+    #[arg(long, visible_alias = "ascii")]
+    plain: bool,
+
+    /// Show only warnings and errors (JSON and Markdown leave passing checks
+    /// out), and suppress the VERDICT line on stderr
+    #[arg(long)]
+    quiet: bool,
+
+    /// Offer to run the fix command of each failing check, then re-check
+    #[arg(long, conflicts_with_all = ["json", "json_compact"])]
+    fix: bool,
+
+    /// After the checks, play a short test tone and ask whether it was heard
+    #[arg(long, conflicts_with_all = ["watch", "list_checks", "explain", "check_deps"])]
+    probe: bool,
+
+    /// Re-run the diagnosis every SECONDS (default 2) until Ctrl-C
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "2",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = ["json", "json_compact", "markdown", "fix"]
+    )]
+    watch: Option<u64>,
+
+    /// Answer each connection on this Unix socket with a fresh JSON report, until SIGTERM
+    #[arg(
+        long,
+        value_name = "SOCKET",
+        conflicts_with_all = ["watch", "fix", "probe", "output", "markdown", "oneline", "list_checks", "explain", "check_deps"]
+    )]
+    serve: Option<PathBuf>,
+
+    /// Lowest check status that makes the exit code non-zero
+    #[arg(long, value_enum, value_name = "STATUS", default_value_t = FailOn::Error)]
+    fail_on: FailOn,
+
+    /// Write the report to this file (created or truncated) instead of stdout
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["watch", "list_checks", "explain", "check_deps"])]
+    output: Option<PathBuf>,
+
+    /// Exit code when the worst check status is a warning (0-255)
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    warning_exit_code: u8,
+
+    /// Exit code when any check reports an error (0-255)
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    error_exit_code: u8,
+}
+
+/// Which check statuses fail the run (`--fail-on`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum FailOn {
+    /// Warnings and errors exit non-zero
+    Warning,
+    /// Only errors exit non-zero
+    Error,
+    /// Always exit 0
+    Never,
 }
 
 fn main() {
-    let args = Args::parse();
-    let check_results = checks::run_all_checks();
+    let mut args = Args::parse();
+    args.debug |= args.verbose > 0;
+    runner::set_verbosity(args.verbose);
+
+    let mut config = checks::CheckConfig {
+        program_paths: [("pactl", &args.pactl_path), ("aplay", &args.aplay_path)]
+            .into_iter()
+            .filter_map(|(program, path)| path.clone().map(|path| (program.to_string(), path)))
+            .collect(),
+        ..Default::default()
+    };
+    if let Some(apps) = args.direct_alsa_apps.clone() {
+        config.direct_alsa_apps = apps;
+    }
+
+    config.sink = args.sink.clone();
+    config.log_since = args.log_since;
+    // The last sink that worked here says nothing about a dumped system
+    if args.from_dump.is_none() {
+        config.last_sink = state::load_last_sink();
+    }
+    config.from_dump = args.from_dump.clone();
+    config.pid = args.pid;
+
+    let filter = match (args.only.clone(), args.skip.clone()) {
+        (Some(names), _) => checks::CheckFilter::Only(names),
+        (None, Some(names)) => checks::CheckFilter::Skip(names),
+        (None, None) => checks::CheckFilter::All,
+    };
+
+    let unknown = filter.unknown_names();
+    if !unknown.is_empty() {
+        Args::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!(
+                    "unknown check name(s): {}\n\nValid checks: {}",
+                    unknown.join(", "),
+                    checks::check_names().join(", ")
+                ),
+            )
+            .exit();
+    }
+
+    if let Some(ref name) = args.explain {
+        match checks::check_explanation(name) {
+            Some(explanation) => output::print_explanation(name, explanation),
+            None => Args::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!(
+                        "unknown check name: {}\n\nValid checks: {}",
+                        name,
+                        checks::check_names().join(", ")
+                    ),
+                )
+                .exit(),
+        }
+        return;
+    }
+
+    if args.list_checks {
+        output::print_check_list(
+            &checks::list_checks(&filter),
+            args.json || args.json_compact,
+            args.json_compact,
+        );
+        return;
+    }
+
+    if args.check_deps {
+        let runner = config.system_runner();
+        let programs: Vec<_> = checks::check_programs()
+            .into_iter()
+            .map(|(program, users)| (program, runner.is_installed(program), users))
+            .collect();
+        output::print_dependencies(&programs, args.plain);
+        return;
+    }
+
+    // A dumped system's sinks can't be listed, so a bad --sink shows up as a
+    // missing sink in the report instead
+    if let (Some(ref sink), None) = (&args.sink, &args.from_dump) {
+        match checks::sink_names(&config.system_runner()) {
+            None => Args::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    "cannot list sinks to check --sink (is pactl installed and the audio server running?)",
+                )
+                .exit(),
+            Some(names) if !names.contains(sink) => Args::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!(
+                        "unknown sink '{}'\n\nAvailable sinks: {}",
+                        sink,
+                        names.join(", ")
+                    ),
+                )
+                .exit(),
+            Some(_) => {}
+        }
+    }
+
+    if let Some(ref path) = args.serve {
+        if let Err(e) = serve::run(path, || {
+            printed_report(&args, &collect_report(&args, &config, &filter))
+        }) {
+            eprintln!("error: cannot serve on {}: {}", path.display(), e);
+            std::process::exit(2);
+        }
+        return;
+    }
+
+    if let Some(seconds) = args.watch {
+        let options = human_options(&args);
+        watch::run(Duration::from_secs(seconds), || {
+            let report = printed_report(&args, &collect_report(&args, &config, &filter));
+            // A closed stdout ends the session like Ctrl-C would
+            if output::print_human(&mut io::stdout(), &report, options).is_err() {
+                std::process::exit(0);
+            }
+        });
+        return;
+    }
+
+    // Open the file before running checks so a bad path fails fast
+    let mut out: Box<dyn Write> = match args.output {
+        Some(ref path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("error: cannot write to {}: {}", path.display(), e);
+                std::process::exit(2);
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+
+    // The spinner goes to stderr and is erased; it never runs when the
+    // output is piped, machine-readable, quiet, or written to a file
+    let show_spinner = progress::is_interactive()
+        && !(args.json || args.json_compact || args.quiet || args.oneline)
+        && args.output.is_none();
+    let mut report = progress::with_spinner(show_spinner, args.plain, || {
+        collect_report(&args, &config, &filter)
+    });
+
+    // The only check that makes sound, so it never runs without --probe
+    if args.probe {
+        let mut checks = std::mem::take(&mut report.checks);
+        checks.push(probe::run_probe());
+        let pw_dump = report.pw_dump.take();
+        report = report::build_report(checks);
+        report.pw_dump = pw_dump;
+        finish_report(&args, &mut report);
+    }
+
+    // Machine and paste formats drop the per-check list for --summary-only
+    // and the Ok checks for --quiet; the full report stays intact for the
+    // verdict line, and --fix works from the unredacted original
+    let printed = printed_report(&args, &report);
+    let filtered_report;
+    let shown = if args.summary_only || args.quiet {
+        filtered_report = types::DiagnosticReport {
+            checks: printed
+                .checks
+                .iter()
+                .filter(|c| !args.summary_only && c.status != CheckStatus::Ok)
+                .cloned()
+                .collect(),
+            ..printed.clone()
+        };
+        &filtered_report
+    } else {
+        &printed
+    };
+
+    let written = if args.json || args.json_compact {
+        output::print_json(&mut out, shown, args.json_compact)
+    } else if args.markdown {
+        output::print_markdown(&mut out, shown)
+    } else if args.oneline {
+        output::print_oneline(&mut out, &printed)
+    } else {
+        output::print_human(&mut out, &printed, human_options(&args))
+    };
+    if let Err(e) = written.and_then(|_| out.flush()) {
+        eprintln!("error: cannot write output: {}", e);
+        std::process::exit(2);
+    }
+
+    if args.fix {
+        let checks = fix::offer_fixes(&report.checks, &config);
+        let pw_dump = report.pw_dump.take();
+        report = report::build_report(checks);
+        report.pw_dump = pw_dump;
+        finish_report(&args, &mut report);
+    }
+
+    // Remember a default sink that checked out, so a later run can tell when
+    // it silently moved (e.g. to HDMI after resume)
+    let default_sink_ok = report
+        .checks
+        .iter()
+        .any(|c| c.name == "default_sink" && c.status == CheckStatus::Ok);
+    if default_sink_ok && args.sink.is_none() && args.from_dump.is_none() {
+        if let Some(sink) = checks::default_sink_name(&config.system_runner()) {
+            let _ = state::save_last_sink(&sink);
+        }
+    }
+
+    if !(args.quiet || args.oneline) {
+        output::print_verdict(&printed_report(&args, &report));
+    }
+
+    let exit_code = match (report.worst_status(), args.fail_on) {
+        (CheckStatus::Ok, _) | (_, FailOn::Never) | (CheckStatus::Warning, FailOn::Error) => 0,
+        (CheckStatus::Warning, FailOn::Warning) => args.warning_exit_code,
+        (CheckStatus::Error, _) => args.error_exit_code,
+    };
+    std::process::exit(exit_code.into());
+}
+
+/// Run the selected checks and build the report, with debug info and
+/// pw-dump handled per the flags.
+fn collect_report(
+    args: &Args,
+    config: &checks::CheckConfig,
+    filter: &checks::CheckFilter,
+) -> types::DiagnosticReport {
+    let check_results = checks::run_all_checks(config, filter);
     let mut report = report::build_report(check_results);
 
+    if args.pw_dump {
+        report.pw_dump = Some(pw_dump::collect_pw_dump());
+    }
+
+    finish_report(args, &mut report);
+    report
+}
+
+/// Drop debug info from a freshly built report unless `--debug` was given.
+/// Run again whenever the report is rebuilt.
+fn finish_report(args: &Args, report: &mut types::DiagnosticReport) {
     if !args.debug {
         for check in &mut report.checks {
             check.debug_info = None;
         }
     }
+}
 
-    if args.json {
-        output::print_json(&report);
-    } else {
-        output::print_human(&report, args.debug);
+/// The copy of `report` that gets printed or served, with `--stable`,
+/// `--anonymize`, and `--max-debug-bytes` applied. Rebuilding the report after
+/// `--probe` or `--fix` starts from the original, so the probable cause stays
+/// the first error detected, and `--fix` keeps running the original's
+/// commands, since a redacted device name would make them act on nothing.
+fn printed_report(args: &Args, report: &types::DiagnosticReport) -> types::DiagnosticReport {
+    let mut printed = report.clone();
+
+    if args.stable {
+        output::stabilize(&mut printed);
+    }
+
+    if args.anonymize {
+        output::anonymize(&mut printed);
+    }
+
+    // After redaction, so truncation can't cut a MAC address past recognition
+    report::apply_debug_limit(&mut printed, args.max_debug_bytes);
+    printed
+}
+
+fn human_options(args: &Args) -> output::HumanOptions {
+    output::HumanOptions {
+        debug: args.debug,
+        summary_only: args.summary_only,
+        // A file is never a terminal, so --output never gets ANSI codes
+        color: args.output.is_none() && color::should_color(args.no_color),
+        plain: args.plain,
+        quiet: args.quiet,
+    }
+}
+
+/// Parse a `--pactl-path`-style option: an existing, executable file.
+fn parse_executable(value: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(value);
+    let metadata =
+        std::fs::metadata(&path).map_err(|e| format!("cannot use '{}': {}", value, e))?;
+
+    if !metadata.is_file() {
+        return Err(format!("'{}' is not a file", value));
+    }
+    if metadata.permissions().mode() & 0o111 == 0 {
+        return Err(format!("'{}' is not executable", value));
+    }
+    Ok(path)
+}
+
+/// Parse a `--from-dump` option: an existing directory.
+fn parse_directory(value: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(value);
+    let metadata =
+        std::fs::metadata(&path).map_err(|e| format!("cannot use '{}': {}", value, e))?;
+
+    if !metadata.is_dir() {
+        return Err(format!("'{}' is not a directory", value));
+    }
+    Ok(path)
+}
+
+/// Parse a `--log-since` duration: a positive number with an `s`, `m`, `h`,
+/// or `d` suffix.
+fn parse_log_since(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("'{}' is not a duration like 30s, 10m, 1h, or 2d", value);
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| invalid())?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    if amount == 0 {
+        return Err(invalid());
+    }
+    amount
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use why_no_sound::types::CheckResult;
+
+    #[test]
+    fn anonymize_leaves_the_fix_commands_intact() {
+        let args = Args::parse_from(["why-no-sound", "--anonymize", "--fix"]);
+        let report = report::build_report(vec![CheckResult::warning(
+            "bluetooth_profile",
+            "Bluetooth device 'WH-1000XM4' uses the SBC codec, but AAC is available",
+            "Switch the codec: pactl set-card-profile bluez_card.AA_BB_CC_DD_EE_FF a2dp-sink-aac",
+        )
+        .with_fix(&[
+            "pactl",
+            "set-card-profile",
+            "bluez_card.AA_BB_CC_DD_EE_FF",
+            "a2dp-sink-aac",
+        ])]);
+
+        let printed = printed_report(&args, &report);
+
+        let printed_fix = printed.checks[0].fix_command.as_ref().unwrap();
+        assert!(!printed_fix[2].contains("AA_BB_CC_DD_EE_FF"));
+        assert!(!printed.checks[0]
+            .suggestion
+            .as_ref()
+            .unwrap()
+            .contains("AA_BB"));
+        // The report --fix runs from still names the real device
+        assert_eq!(
+            report.checks[0].fix_command.as_ref().unwrap()[2],
+            "bluez_card.AA_BB_CC_DD_EE_FF"
+        );
     }
 }
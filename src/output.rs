@@ -1,40 +1,134 @@
-//! Output rendering for human and JSON formats.
+//! Output rendering for human, Markdown, and JSON formats.
 
-use crate::types::{CheckStatus, DiagnosticReport};
+use std::io::{self, Write};
 
-/// Print the report in human-readable format.
-pub fn print_human(report: &DiagnosticReport, debug: bool) {
-    println!();
-    println!("🔊 why-no-sound — Linux Audio Diagnostic");
-    println!("─────────────────────────────────────────");
-    println!();
+use crate::color;
+use crate::types::{CheckInfo, CheckStatus, DiagnosticReport};
 
-    // Print each check result
+/// Options controlling human-readable rendering.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HumanOptions {
+    /// Show raw debug info under each check.
+    pub debug: bool,
+    /// Skip the per-check lines and print only the diagnosis block.
+    pub summary_only: bool,
+    /// Colorize status lines and the diagnosis banner with ANSI codes.
+    pub color: bool,
+    /// Use textual markers instead of emoji and box-drawing characters.
+    pub plain: bool,
+    /// Show only warnings and errors; a healthy system gets a single line.
+    pub quiet: bool,
+}
+
+impl HumanOptions {
+    fn marker(&self, status: CheckStatus) -> &'static str {
+        if self.plain {
+            status.label()
+        } else {
+            status.emoji()
+        }
+    }
+
+    fn separator(&self) -> &'static str {
+        if self.plain {
+            "-----------------------------------------"
+        } else {
+            "─────────────────────────────────────────"
+        }
+    }
+}
+
+/// Write the report to `out` in human-readable format.
+pub fn print_human(
+    out: &mut dyn Write,
+    report: &DiagnosticReport,
+    options: HumanOptions,
+) -> io::Result<()> {
+    if options.quiet && report.worst_status() == CheckStatus::Ok {
+        let message = color::paint(
+            "Audio system looks healthy",
+            color::for_status(CheckStatus::Ok),
+            options.color,
+        );
+        return writeln!(out, "{} {}", options.marker(CheckStatus::Ok), message);
+    }
+
+    writeln!(out)?;
+    if options.plain {
+        writeln!(out, "why-no-sound - Linux Audio Diagnostic")?;
+    } else {
+        writeln!(out, "🔊 why-no-sound — Linux Audio Diagnostic")?;
+    }
+    writeln!(out, "{}", options.separator())?;
+    writeln!(out)?;
+
+    if !options.summary_only {
+        print_checks(out, report, options)?;
+
+        if let Some(ref pw_dump) = report.pw_dump {
+            writeln!(out)?;
+            writeln!(out, "   [DEBUG: pw-dump]")?;
+            for line in pw_dump.lines() {
+                writeln!(out, "   | {}", line)?;
+            }
+        }
+
+        writeln!(out)?;
+        writeln!(out, "{}", options.separator())?;
+        writeln!(out)?;
+    }
+
+    print_diagnosis(out, report, options)
+}
+
+/// Print each check result with its suggestion and optional debug info.
+fn print_checks(
+    out: &mut dyn Write,
+    report: &DiagnosticReport,
+    options: HumanOptions,
+) -> io::Result<()> {
     for check in &report.checks {
-        let emoji = check.status.emoji();
-        println!("{} {}", emoji, check.message);
+        if options.quiet && check.status == CheckStatus::Ok {
+            continue;
+        }
+
+        let marker = options.marker(check.status);
+        let message = color::paint(
+            &check.message,
+            color::for_status(check.status),
+            options.color,
+        );
+        match check.duration_ms {
+            Some(ms) if options.debug => writeln!(out, "{} {} (took {}ms)", marker, message, ms)?,
+            _ => writeln!(out, "{} {}", marker, message)?,
+        }
 
         if let Some(ref suggestion) = check.suggestion {
-            println!("   👉 Fix: {}", suggestion);
+            let arrow = if options.plain { "->" } else { "👉" };
+            writeln!(out, "   {} Fix: {}", arrow, suggestion)?;
         }
 
-        if debug {
+        if options.debug {
             if let Some(ref debug_info) = check.debug_info {
-                println!();
-                println!("   [DEBUG: {}]", check.name);
+                writeln!(out)?;
+                writeln!(out, "   [DEBUG: {}]", check.name)?;
                 for line in debug_info.lines() {
-                    println!("   | {}", line);
+                    writeln!(out, "   | {}", line)?;
                 }
-                println!();
+                writeln!(out)?;
             }
         }
     }
 
-    // Print summary
-    println!();
-    println!("─────────────────────────────────────────");
-    println!();
+    Ok(())
+}
 
+/// Print the overall diagnosis, probable cause, and suggested fixes.
+fn print_diagnosis(
+    out: &mut dyn Write,
+    report: &DiagnosticReport,
+    options: HumanOptions,
+) -> io::Result<()> {
     // Determine overall status
     let has_errors = report.checks.iter().any(|c| c.status == CheckStatus::Error);
     let has_warnings = report
@@ -42,39 +136,631 @@ pub fn print_human(report: &DiagnosticReport, debug: bool) {
         .iter()
         .any(|c| c.status == CheckStatus::Warning);
 
-    if has_errors {
-        println!("❌ DIAGNOSIS: Issues detected");
+    let (banner, status) = if has_errors {
+        ("DIAGNOSIS: Issues detected", CheckStatus::Error)
     } else if has_warnings {
-        println!("⚠️  DIAGNOSIS: Potential issues");
+        ("DIAGNOSIS: Potential issues", CheckStatus::Warning)
     } else {
-        println!("✅ DIAGNOSIS: System looks healthy");
+        ("DIAGNOSIS: System looks healthy", CheckStatus::Ok)
+    };
+    // The warning emoji renders narrow, so pad it to line up with the others
+    let marker = match (options.plain, status) {
+        (false, CheckStatus::Warning) => "⚠️ ",
+        _ => options.marker(status),
+    };
+
+    writeln!(out, "{}", status_counts(report, options))?;
+    writeln!(out)?;
+
+    let banner_color = format!("{}{}", color::BOLD, color::for_status(status));
+    writeln!(
+        out,
+        "{} {}",
+        marker,
+        color::paint(banner, &banner_color, options.color)
+    )?;
+
+    writeln!(out)?;
+    writeln!(out, "{}", report.summary)?;
+
+    if let Some(ref cause) = report.probable_cause {
+        writeln!(out)?;
+        let label = format!(
+            "Probable root cause (confidence: {:.0}%):",
+            report.confidence * 100.0
+        );
+        if options.plain {
+            writeln!(out, "{}", label)?;
+        } else {
+            writeln!(out, "🎯 {}", label)?;
+        }
+        writeln!(out, "   {}", cause)?;
     }
 
-    println!();
-    println!("{}", report.summary);
+    if !report.suggested_fixes.is_empty() {
+        writeln!(out)?;
+        if options.plain {
+            writeln!(out, "Suggested fixes (in order):")?;
+        } else {
+            writeln!(out, "📋 Suggested fixes (in order):")?;
+        }
+        for (i, fix) in report.suggested_fixes.iter().enumerate() {
+            writeln!(out, "   {}. {}", i + 1, fix)?;
+        }
+    }
+
+    writeln!(out)
+}
+
+/// A legend like `3 ok · 2 warnings · 1 error`, each non-zero count in its
+/// status color.
+fn status_counts(report: &DiagnosticReport, options: HumanOptions) -> String {
+    let parts: Vec<String> = [
+        (CheckStatus::Ok, "ok", "ok"),
+        (CheckStatus::Warning, "warning", "warnings"),
+        (CheckStatus::Error, "error", "errors"),
+    ]
+    .iter()
+    .map(|&(status, singular, plural)| {
+        let count = report.checks.iter().filter(|c| c.status == status).count();
+        let text = format!("{} {}", count, if count == 1 { singular } else { plural });
+        color::paint(&text, color::for_status(status), options.color && count > 0)
+    })
+    .collect();
+
+    parts.join(if options.plain { ", " } else { " · " })
+}
+
+/// Write the report to `out` as Markdown for pasting into bug reports and chat.
+///
+/// Debug info and the pw-dump, when present, go in collapsible `<details>` blocks.
+pub fn print_markdown(out: &mut dyn Write, report: &DiagnosticReport) -> io::Result<()> {
+    writeln!(out, "## why-no-sound report")?;
+    writeln!(out)?;
+    writeln!(out, "{}", report.summary)?;
+
+    if !report.checks.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "| Status | Check | Message |")?;
+        writeln!(out, "|--------|-------|---------|")?;
+        for check in &report.checks {
+            writeln!(
+                out,
+                "| {} | `{}` | {} |",
+                check.status.emoji(),
+                check.name,
+                escape_table_cell(&check.message)
+            )?;
+        }
+    }
 
     if let Some(ref cause) = report.probable_cause {
-        println!();
-        println!("🎯 Probable root cause:");
-        println!("   {}", cause);
+        writeln!(out)?;
+        writeln!(
+            out,
+            "**Probable root cause:** {} (confidence: {:.0}%)",
+            cause,
+            report.confidence * 100.0
+        )?;
     }
 
     if !report.suggested_fixes.is_empty() {
-        println!();
-        println!("📋 Suggested fixes (in order):");
+        writeln!(out)?;
+        writeln!(out, "### Suggested fixes")?;
+        writeln!(out)?;
         for (i, fix) in report.suggested_fixes.iter().enumerate() {
-            println!("   {}. {}", i + 1, fix);
+            writeln!(out, "{}. {}", i + 1, fix)?;
+        }
+    }
+
+    for check in &report.checks {
+        if let Some(ref debug_info) = check.debug_info {
+            print_details(out, &format!("Debug: {}", check.name), debug_info)?;
+        }
+    }
+
+    if let Some(ref pw_dump) = report.pw_dump {
+        print_details(out, "pw-dump", pw_dump)?;
+    }
+
+    Ok(())
+}
+
+fn print_details(out: &mut dyn Write, summary: &str, body: &str) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(out, "<details><summary>{}</summary>", summary)?;
+    writeln!(out)?;
+    writeln!(out, "```")?;
+    writeln!(out, "{}", body.trim_end())?;
+    writeln!(out, "```")?;
+    writeln!(out)?;
+    writeln!(out, "</details>")
+}
+
+/// Keep a message on one table row: pipes would start a new cell.
+fn escape_table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Labels whose number changes from run to run (object indices, client
+/// ids, PIDs) without the system state changing.
+const VOLATILE_NUMBER_PREFIXES: &[&str] = &[
+    "#",
+    "Sink: ",
+    "Source: ",
+    "Client: ",
+    "Owner Module: ",
+    "Module: ",
+    "owner_pid: ",
+    "object.id = \"",
+    "object.serial = \"",
+    "client.id = \"",
+    "node.id = \"",
+    "application.process.id = \"",
+    "\"id\": ",
+    "\"object.serial\": ",
+    "\"client.id\": ",
+];
+
+/// Make the report byte-stable across runs of the same system state
+/// (`--stable`): drop timings, replace volatile numbers in debug output and
+/// pw-dump with `N`, mask server-assigned ids in messages, the summary, the
+/// cause, and the suggested fixes, and sort the checks by name. The fixes
+/// keep their order, since earlier ones must be applied first.
+pub fn stabilize(report: &mut DiagnosticReport) {
+    for check in &mut report.checks {
+        check.duration_ms = None;
+        check.message = mask_message_ids(&check.message);
+        if let Some(ref mut debug_info) = check.debug_info {
+            *debug_info = mask_volatile_numbers(debug_info);
+        }
+    }
+    report.checks.sort_by(|a, b| a.name.cmp(&b.name));
+    report.summary = mask_message_ids(&report.summary);
+    report.probable_cause = report.probable_cause.as_deref().map(mask_message_ids);
+    for fix in &mut report.suggested_fixes {
+        *fix = mask_message_ids(fix);
+    }
+
+    if let Some(ref mut pw_dump) = report.pw_dump {
+        *pw_dump = mask_volatile_numbers(pw_dump);
+    }
+}
+
+/// Text in a message right before an id the server assigns anew whenever a
+/// device or stream appears (`node 52`, `Sink #48`).
+const MESSAGE_ID_PREFIXES: &[&str] = &["node ", "#"];
+
+/// Replace server-assigned ids in `message` with `N`. Levels, durations, and
+/// counts describe the system's state, so a diff between runs should show them.
+fn mask_message_ids(message: &str) -> String {
+    mask_numbers_after(message, MESSAGE_ID_PREFIXES)
+}
+
+fn mask_volatile_numbers(text: &str) -> String {
+    mask_numbers_after(text, VOLATILE_NUMBER_PREFIXES)
+}
+
+/// Replace the number following any of `prefixes` in `text` with `N`.
+fn mask_numbers_after(text: &str, prefixes: &[&str]) -> String {
+    let mut masked = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        match prefixes.iter().find(|p| rest.starts_with(**p)) {
+            Some(prefix) => {
+                masked.push_str(prefix);
+                rest = &rest[prefix.len()..];
+                let digits =
+                    rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                if digits > 0 {
+                    masked.push('N');
+                    rest = &rest[digits..];
+                }
+            }
+            None => {
+                masked.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
         }
     }
 
-    println!();
+    masked
 }
 
-/// Print the report as JSON.
-pub fn print_json(report: &DiagnosticReport) {
+/// Placeholders `anonymize` substitutes for personal details.
+const MAC_PLACEHOLDER: &str = "XX";
+const USER_PLACEHOLDER: &str = "USER";
+const HOST_PLACEHOLDER: &str = "HOSTNAME";
+
+/// Strip personal details from the report for posting publicly
+/// (`--anonymize`): Bluetooth MAC addresses, the user name (including in
+/// `/home/...` paths), and the host name, in every message, suggestion, fix
+/// command, debug section, and pw-dump.
+pub fn anonymize(report: &mut DiagnosticReport) {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .ok()
+        // Redacting "root" would also hit "root cause" and the like
+        .filter(|u| u != "root");
+    let host = std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty() && h != "localhost");
+
+    let redact = |text: &mut String| {
+        *text = anonymize_text(text, user.as_deref(), host.as_deref());
+    };
+
+    for check in &mut report.checks {
+        redact(&mut check.message);
+        check.suggestion.iter_mut().for_each(redact);
+        check.debug_info.iter_mut().for_each(redact);
+        check.fix_command.iter_mut().flatten().for_each(redact);
+    }
+    redact(&mut report.summary);
+    report.probable_cause.iter_mut().for_each(redact);
+    report.suggested_fixes.iter_mut().for_each(redact);
+    report.pw_dump.iter_mut().for_each(redact);
+}
+
+/// Redact MAC addresses, `/home/<name>` paths, and whole-word occurrences of
+/// `user` and `host` in `text`.
+fn anonymize_text(text: &str, user: Option<&str>, host: Option<&str>) -> String {
+    let mut redacted = mask_mac_addresses(text);
+    redacted = mask_home_dirs(&redacted);
+    if let Some(user) = user {
+        redacted = replace_word(&redacted, user, USER_PLACEHOLDER);
+    }
+    if let Some(host) = host {
+        redacted = replace_word(&redacted, host, HOST_PLACEHOLDER);
+    }
+    redacted
+}
+
+/// Replace each octet of `AA:BB:CC:DD:EE:FF` or `AA_BB_CC_DD_EE_FF` (the
+/// form in BlueZ card and sink names) with `XX`, keeping the separators.
+fn mask_mac_addresses(text: &str) -> String {
+    const MAC_LEN: usize = 17;
+    let bytes = text.as_bytes();
+    let mut masked = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let candidate = &bytes[i..bytes.len().min(i + MAC_LEN)];
+        let separator = candidate.get(2).copied();
+        let is_mac = candidate.len() == MAC_LEN
+            && matches!(separator, Some(b':') | Some(b'_'))
+            && candidate.iter().enumerate().all(|(j, &b)| {
+                if j % 3 == 2 {
+                    Some(b) == separator
+                } else {
+                    b.is_ascii_hexdigit()
+                }
+            })
+            && !(i > 0 && bytes[i - 1].is_ascii_alphanumeric())
+            && !bytes
+                .get(i + MAC_LEN)
+                .is_some_and(|b| b.is_ascii_alphanumeric());
+
+        if is_mac {
+            let separator = separator.map(char::from).unwrap_or(':').to_string();
+            masked.push_str(&[MAC_PLACEHOLDER; 6].join(&separator));
+            i += MAC_LEN;
+        } else {
+            // Advance by a whole character so multi-byte text stays intact
+            let c = text[i..].chars().next().unwrap_or_default();
+            masked.push(c);
+            i += c.len_utf8().max(1);
+        }
+    }
+
+    masked
+}
+
+/// Replace the directory name after each `/home/` with `USER`.
+fn mask_home_dirs(text: &str) -> String {
+    let mut masked = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(pos) = rest.find("/home/") {
+        let after = &rest[pos + "/home/".len()..];
+        let name_len = after
+            .find(|c: char| c == '/' || c == '"' || c == '\'' || c.is_whitespace())
+            .unwrap_or(after.len());
+        masked.push_str(&rest[..pos + "/home/".len()]);
+        if name_len > 0 {
+            masked.push_str(USER_PLACEHOLDER);
+        }
+        rest = &after[name_len..];
+    }
+
+    masked.push_str(rest);
+    masked
+}
+
+/// Replace occurrences of `word` that aren't part of a longer word.
+fn replace_word(text: &str, word: &str, placeholder: &str) -> String {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(pos) = rest.find(word) {
+        let before = rest[..pos].chars().next_back();
+        let after = rest[pos + word.len()..].chars().next();
+        replaced.push_str(&rest[..pos]);
+        if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+            replaced.push_str(word);
+        } else {
+            replaced.push_str(placeholder);
+        }
+        rest = &rest[pos + word.len()..];
+    }
+
+    replaced.push_str(rest);
+    replaced
+}
+
+/// Write the report to `out` as JSON, pretty-printed unless `compact` is set.
+pub fn print_json(out: &mut dyn Write, report: &DiagnosticReport, compact: bool) -> io::Result<()> {
     // Create a JSON-friendly version without debug info unless needed
-    match serde_json::to_string_pretty(report) {
-        Ok(json) => println!("{}", json),
-        Err(e) => eprintln!("Error serializing report to JSON: {}", e),
+    let json = if compact {
+        serde_json::to_string(report)
+    } else {
+        serde_json::to_string_pretty(report)
+    };
+    match json {
+        Ok(json) => writeln!(out, "{}", json),
+        Err(e) => {
+            eprintln!("Error serializing report to JSON: {}", e);
+            Ok(())
+        }
+    }
+}
+
+/// Write a single summary line for pasting into chat (`--oneline`).
+///
+/// Format: `why-no-sound: <OK|WARNING|ERROR> <headline>; <N> err <N> warn`.
+/// The headline is the probable cause, else the first warning, else
+/// "no problems found".
+pub fn print_oneline(out: &mut dyn Write, report: &DiagnosticReport) -> io::Result<()> {
+    let count = |status: CheckStatus| report.checks.iter().filter(|c| c.status == status).count();
+    let headline = report
+        .probable_cause
+        .as_deref()
+        .or_else(|| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.status == CheckStatus::Warning)
+                .map(|c| c.message.as_str())
+        })
+        .unwrap_or("no problems found");
+
+    writeln!(
+        out,
+        "why-no-sound: {} {}; {} err {} warn",
+        report.worst_status().token().to_uppercase(),
+        headline.replace('\n', " "),
+        count(CheckStatus::Error),
+        count(CheckStatus::Warning)
+    )
+}
+
+/// Print the available checks, one per line, or as JSON when `json` is set.
+pub fn print_check_list(checks: &[CheckInfo], json: bool, compact: bool) {
+    if json {
+        let json = if compact {
+            serde_json::to_string(checks)
+        } else {
+            serde_json::to_string_pretty(checks)
+        };
+        match json {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Error serializing check list to JSON: {}", e),
+        }
+        return;
+    }
+
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for check in checks {
+        println!(
+            "{:width$}  {}{}",
+            check.name,
+            check.description,
+            if check.enabled { "" } else { " (disabled)" },
+            width = width
+        );
+    }
+}
+
+/// Print a check's `--explain` text, word-wrapped for a terminal.
+pub fn print_explanation(name: &str, explanation: &str) {
+    const WIDTH: usize = 78;
+
+    println!("{}\n", name);
+
+    let mut line = String::new();
+    for word in explanation.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > WIDTH {
+            println!("{}", line);
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        println!("{}", line);
+    }
+}
+
+/// Print which external programs are installed and the checks each one powers.
+/// `programs` holds (program, installed, checks) entries.
+pub fn print_dependencies(programs: &[(&str, bool, Vec<&str>)], plain: bool) {
+    let width = programs.iter().map(|(p, _, _)| p.len()).max().unwrap_or(0);
+
+    for (program, installed, checks) in programs {
+        let status = if *installed {
+            CheckStatus::Ok
+        } else {
+            CheckStatus::Warning
+        };
+        // Pad the text markers so the columns line up
+        let marker = if plain {
+            format!("{:6}", status.label())
+        } else {
+            status.emoji().to_string()
+        };
+        println!(
+            "{} {:width$}  {:9}  {}",
+            marker,
+            program,
+            if *installed { "found" } else { "missing" },
+            checks.join(", "),
+            width = width
+        );
+    }
+}
+
+/// Print the one-line verdict to stderr.
+///
+/// Format: `VERDICT=<ok|warning|error>` followed by ` cause="<probable cause>"`
+/// when a probable cause was identified. Quotes and backslashes in the cause
+/// are backslash-escaped.
+pub fn print_verdict(report: &DiagnosticReport) {
+    let mut line = format!("VERDICT={}", report.worst_status().token());
+
+    if let Some(ref cause) = report.probable_cause {
+        let escaped = cause.replace('\\', "\\\\").replace('"', "\\\"");
+        line.push_str(&format!(" cause=\"{}\"", escaped));
+    }
+
+    eprintln!("{}", line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::build_report;
+    use crate::types::CheckResult;
+
+    #[test]
+    fn message_masks_only_server_ids() {
+        assert_eq!(
+            mask_message_ids("Default sink: Speakers (node 52)"),
+            "Default sink: Speakers (node N)"
+        );
+        assert_eq!(
+            mask_message_ids("'Spotify' plays to Sink #52"),
+            "'Spotify' plays to Sink #N"
+        );
+        // Levels, durations, and counts are state a diff should catch
+        assert_eq!(
+            mask_message_ids("Volume is very low (3%)"),
+            "Volume is very low (3%)"
+        );
+        assert_eq!(
+            mask_message_ids("Quantum 1024 adds 21.3 ms of latency"),
+            "Quantum 1024 adds 21.3 ms of latency"
+        );
+        assert_eq!(
+            mask_message_ids("2 audio device(s) detected on hw:1"),
+            "2 audio device(s) detected on hw:1"
+        );
+    }
+
+    #[test]
+    fn debug_output_masks_indices() {
+        assert_eq!(
+            mask_volatile_numbers("Sink #48\n\tClient: 70\n\tSink: 48"),
+            "Sink #N\n\tClient: N\n\tSink: N"
+        );
+    }
+
+    #[test]
+    fn mac_addresses_are_masked_in_both_forms() {
+        assert_eq!(
+            mask_mac_addresses("bluez_output.AA_BB_CC_DD_EE_0F.1 at 00:1a:7d:da:71:13"),
+            "bluez_output.XX_XX_XX_XX_XX_XX.1 at XX:XX:XX:XX:XX:XX"
+        );
+        // Part of a longer word, or the wrong length
+        assert_eq!(
+            mask_mac_addresses("x00:11:22:33:44:55 AA:BB:CC:DD:EE"),
+            "x00:11:22:33:44:55 AA:BB:CC:DD:EE"
+        );
+    }
+
+    #[test]
+    fn anonymize_text_redacts_user_host_and_home() {
+        assert_eq!(
+            anonymize_text(
+                "alice@laptop: /home/alice/.config/pulse/client.conf owned by alice",
+                Some("alice"),
+                Some("laptop")
+            ),
+            "USER@HOSTNAME: /home/USER/.config/pulse/client.conf owned by USER"
+        );
+        // Only whole words are replaced
+        assert_eq!(
+            anonymize_text("malice in alice-wonderland", Some("alice"), None),
+            "malice in alice-wonderland"
+        );
+    }
+
+    #[test]
+    fn stabilized_report_is_sorted_and_untimed() {
+        let mut first = CheckResult::ok("mute_state", "Output is not muted (volume: 65%)");
+        first.duration_ms = Some(12);
+        let mut report = build_report(vec![
+            first,
+            CheckResult::ok("audio_stack", "PipeWire and WirePlumber are running"),
+        ]);
+        stabilize(&mut report);
+
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["audio_stack", "mute_state"]);
+        assert_eq!(report.checks[1].duration_ms, None);
+        assert_eq!(
+            report.checks[1].message,
+            "Output is not muted (volume: 65%)"
+        );
+    }
+
+    #[test]
+    fn stabilized_fixes_keep_their_order_and_mask_ids() {
+        let mut report = build_report(vec![
+            CheckResult::warning("sink_inputs", "'Spotify' plays to Sink #52", "Move it"),
+            CheckResult::error("audio_stack", "No audio server detected", "Start PipeWire"),
+        ]);
+        stabilize(&mut report);
+
+        assert_eq!(
+            report.suggested_fixes,
+            vec![
+                "No audio server detected: Start PipeWire",
+                "'Spotify' plays to Sink #N: Move it",
+            ]
+        );
+        assert_eq!(
+            report.probable_cause.as_deref(),
+            Some("No audio server detected")
+        );
+    }
+
+    #[test]
+    fn json_carries_condition_codes_only_on_findings() {
+        let report = build_report(vec![
+            CheckResult::error("mute_state", "Output is muted", "Unmute it")
+                .with_code("mute.sink_muted"),
+            CheckResult::ok("audio_stack", "PipeWire is running"),
+        ]);
+        let mut out = Vec::new();
+        print_json(&mut out, &report, true).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(json["checks"][0]["code"], "mute.sink_muted");
+        assert!(json["checks"][1].get("code").is_none());
     }
 }
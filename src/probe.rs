@@ -0,0 +1,91 @@
+//! Opt-in `--probe`: plays a test tone and asks whether it was heard.
+
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use why_no_sound::runner::{run_command_timeout, CommandRunner, SystemRunner};
+use why_no_sound::types::{CheckResult, Subsystem};
+
+const CHECK_NAME: &str = "playback_probe";
+
+/// How long `speaker-test` may run before it is killed. One loop of the sine
+/// test plays every channel in turn, so this leaves room for surround setups.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Play a 440 Hz tone through the ALSA `default` device (which the audio
+/// server routes to the default output) and ask the user whether they heard it.
+///
+/// The prompt goes to stderr so it never ends up in JSON or Markdown output.
+pub fn run_probe() -> CheckResult {
+    probe().with_subsystem(Subsystem::Device)
+}
+
+fn probe() -> CheckResult {
+    if !SystemRunner::default().is_installed("speaker-test") {
+        return CheckResult::ok(
+            CHECK_NAME,
+            "Playback not probed (speaker-test not installed)",
+        );
+    }
+
+    eprintln!("\n🔈 Playing a test tone on the default output...");
+    let output = run_command_timeout(
+        "speaker-test",
+        &["-t", "sine", "-f", "440", "-l", "1"],
+        PROBE_TIMEOUT,
+    );
+    let debug_info = format!("speaker-test:\n{}{}\n", output.stdout, output.stderr);
+
+    if output.stderr.contains("Command timed out") {
+        return CheckResult::warning(
+            CHECK_NAME,
+            "Test tone did not finish playing (speaker-test hung)",
+            "The output device may be blocked; check for apps holding it or a stalled audio server",
+        )
+        .with_code("probe.timed_out")
+        .with_debug(debug_info);
+    }
+
+    if !output.success {
+        return CheckResult::error(
+            CHECK_NAME,
+            "Test tone could not be played",
+            "See the speaker-test output with --debug; the default ALSA device may not open",
+        )
+        .with_code("probe.play_failed")
+        .with_debug(debug_info);
+    }
+
+    match ask_heard() {
+        Some(true) => CheckResult::ok(CHECK_NAME, "Test tone was heard").with_debug(debug_info),
+        Some(false) => CheckResult::error(
+            CHECK_NAME,
+            "Test tone played without error but was not heard",
+            "Check speaker/headphone connections, hardware volume knobs, and the selected output",
+        )
+        .with_code("probe.not_heard")
+        .with_debug(debug_info),
+        None => CheckResult::warning(
+            CHECK_NAME,
+            "Test tone played, but nobody confirmed hearing it",
+            "Run --probe from an interactive terminal to answer the prompt",
+        )
+        .with_code("probe.unconfirmed")
+        .with_debug(debug_info),
+    }
+}
+
+/// Ask on stdin. `None` when stdin is closed or unreadable.
+fn ask_heard() -> Option<bool> {
+    eprint!("   Did you hear the tone? [y/n] ");
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    match io::stdin().lock().read_line(&mut answer) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => {
+            let answer = answer.trim().to_lowercase();
+            Some(answer == "y" || answer == "yes")
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Transient "Running checks…" spinner for interactive runs.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Time between spinner frames.
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Don't draw anything for runs that finish faster than this.
+const SHOW_AFTER: Duration = Duration::from_millis(300);
+
+const FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const PLAIN_FRAMES: &[&str] = &["|", "/", "-", "\\"];
+
+/// Whether a spinner can be shown without ending up in piped output: both
+/// stdout (where the report goes) and stderr (where the spinner goes) must
+/// be terminals.
+pub fn is_interactive() -> bool {
+    io::stdout().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Run `work`, showing a spinner on stderr while it takes longer than
+/// `SHOW_AFTER`. The spinner line is erased before returning, so the real
+/// output starts on a clean line. With `enabled` false this just runs `work`.
+pub fn with_spinner<T>(enabled: bool, plain: bool, work: impl FnOnce() -> T) -> T {
+    if !enabled {
+        return work();
+    }
+
+    let done = AtomicBool::new(false);
+    let frames = if plain { PLAIN_FRAMES } else { FRAMES };
+
+    thread::scope(|scope| {
+        let spinner = scope.spawn(|| {
+            let mut waited = Duration::ZERO;
+            let mut drawn = false;
+
+            for frame in frames.iter().cycle() {
+                if done.load(Ordering::SeqCst) {
+                    break;
+                }
+                if waited >= SHOW_AFTER {
+                    eprint!("\r{} Running checks…", frame);
+                    let _ = io::stderr().flush();
+                    drawn = true;
+                }
+                thread::sleep(FRAME_INTERVAL);
+                waited += FRAME_INTERVAL;
+            }
+
+            if drawn {
+                // Carriage return, then erase the whole line
+                eprint!("\r\x1B[2K");
+                let _ = io::stderr().flush();
+            }
+        });
+
+        let result = work();
+        done.store(true, Ordering::SeqCst);
+        let _ = spinner.join();
+        result
+    })
+}
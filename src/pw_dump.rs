@@ -0,0 +1,25 @@
+//! Opt-in capture of the full PipeWire object graph via `pw-dump`.
+
+use crate::runner::{run_command_bounded, DEFAULT_MAX_OUTPUT_BYTES};
+
+/// Run `pw-dump` and return its output, pretty-printed when it is valid JSON.
+/// Never fails - returns a short explanation if pw-dump cannot be run.
+pub fn collect_pw_dump() -> String {
+    let output = run_command_bounded("pw-dump", &[], DEFAULT_MAX_OUTPUT_BYTES);
+
+    if !output.success {
+        return format!("pw-dump failed:\n{}", output.stderr.trim());
+    }
+
+    if output.truncated {
+        return format!(
+            "{}\n... [pw-dump output truncated at {} bytes]",
+            output.stdout, DEFAULT_MAX_OUTPUT_BYTES
+        );
+    }
+
+    match serde_json::from_str::<serde_json::Value>(&output.stdout) {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or(output.stdout),
+        Err(_) => output.stdout,
+    }
+}
@@ -1,6 +1,16 @@
 //! Report aggregation and analysis.
 
-use crate::types::{CheckResult, CheckStatus, DiagnosticReport};
+use crate::checks;
+use crate::types::{worst_status, CheckResult, CheckStatus, DiagnosticReport, SCHEMA_VERSION};
+
+/// Next steps when every check passes but there is still no sound, in the
+/// order worth trying. They stand in for fixes, so JSON consumers get them too.
+const HEALTHY_NEXT_STEPS: &[&str] = &[
+    "Check the app's own output device: open pavucontrol (Playback tab) while it plays and make sure it isn't sent to another device",
+    "Test the server directly: pw-play /usr/share/sounds/alsa/Front_Center.wav (or paplay on PulseAudio); if that is audible, the problem is in the app",
+    "In a browser, check the site's sound permission and that the tab isn't muted",
+    "Check the physical side: speaker power and volume knobs, the cable, and the jack the device is plugged into",
+];
 
 /// Build a diagnostic report from check results.
 pub fn build_report(checks: Vec<CheckResult>) -> DiagnosticReport {
@@ -20,7 +30,7 @@ pub fn build_report(checks: Vec<CheckResult>) -> DiagnosticReport {
 
     // Build summary
     let summary = if errors.is_empty() && warnings.is_empty() {
-        "Audio system appears healthy. If you still have no sound, the issue may be application-specific.".to_string()
+        "Audio system appears healthy. If you still have no sound, the issue may be application-specific; try the steps below.".to_string()
     } else if errors.is_empty() {
         format!(
             "No critical issues found, but {} warning(s) detected that may affect audio.",
@@ -35,16 +45,15 @@ pub fn build_report(checks: Vec<CheckResult>) -> DiagnosticReport {
         )
     };
 
-    // Build ordered fix list (errors first, then warnings)
-    let mut suggested_fixes: Vec<String> = Vec::new();
+    // Build ordered fix list: foundational checks first (a dead audio server
+    // must be fixed before stream routing matters), errors before warnings
+    // within the same tier
+    let mut failing: Vec<&CheckResult> = errors.iter().chain(warnings.iter()).copied().collect();
+    failing.sort_by_key(|c| checks::fix_priority(&c.name));
 
-    for check in &errors {
-        if let Some(ref suggestion) = check.suggestion {
-            suggested_fixes.push(format!("{}: {}", check.message, suggestion));
-        }
-    }
+    let mut suggested_fixes: Vec<String> = Vec::new();
 
-    for check in &warnings {
+    for check in failing {
         if let Some(ref suggestion) = check.suggestion {
             // Don't duplicate fixes
             let fix = format!("{}: {}", check.message, suggestion);
@@ -54,10 +63,148 @@ pub fn build_report(checks: Vec<CheckResult>) -> DiagnosticReport {
         }
     }
 
+    // A healthy report would otherwise end with nothing to try
+    if suggested_fixes.is_empty() && errors.is_empty() && warnings.is_empty() {
+        suggested_fixes = HEALTHY_NEXT_STEPS.iter().map(|s| s.to_string()).collect();
+    }
+
+    let confidence = cause_confidence(&checks);
+
+    let overall_status = worst_status(&checks);
+
     DiagnosticReport {
+        overall_status,
+        schema_version: SCHEMA_VERSION,
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
         checks,
         summary,
         probable_cause,
+        confidence,
         suggested_fixes,
+        pw_dump: None,
+    }
+}
+
+/// How sure we are that the first error is the root cause, from 0.0 to 1.0.
+///
+/// Heuristic: the cause starts at 0.5. Every other warning or error in the
+/// same subsystem (e.g. a muted sink plus 0% volume both implicate volume)
+/// corroborates it and adds 0.2. Every error in a different subsystem is a
+/// competing explanation and subtracts 0.1. The result is clamped to
+/// 0.1–0.95, since a heuristic is never certain. With no error there is no
+/// cause, and the confidence is 0.0.
+fn cause_confidence(checks: &[CheckResult]) -> f32 {
+    let cause = match checks.iter().find(|c| c.status == CheckStatus::Error) {
+        Some(cause) => cause,
+        None => return 0.0,
+    };
+    let cause_subsystem = cause.subsystem;
+
+    let mut confidence: f32 = 0.5;
+    for check in checks {
+        if std::ptr::eq(check, cause) || check.status == CheckStatus::Ok {
+            continue;
+        }
+        if check.subsystem == cause_subsystem {
+            confidence += 0.2;
+        } else if check.status == CheckStatus::Error {
+            confidence -= 0.1;
+        }
+    }
+
+    // Two decimals is all the precision a heuristic has
+    (confidence.clamp(0.1, 0.95) * 100.0).round() / 100.0
+}
+
+/// Start of the note `apply_debug_limit` appends to a cut section.
+const TRUNCATION_MARKER: &str = "\n... [truncated ";
+
+/// Cap every debug section of the report at `max_bytes`, appending a marker
+/// that says how much was cut.
+pub fn apply_debug_limit(report: &mut DiagnosticReport, max_bytes: usize) {
+    for check in &mut report.checks {
+        if let Some(ref mut debug_info) = check.debug_info {
+            truncate_with_marker(debug_info, max_bytes);
+        }
+    }
+
+    if let Some(ref mut pw_dump) = report.pw_dump {
+        truncate_with_marker(pw_dump, max_bytes);
+    }
+}
+
+fn truncate_with_marker(text: &mut String, max_bytes: usize) {
+    if text.len() <= max_bytes {
+        return;
+    }
+
+    // Already cut by an earlier pass over the same report
+    let marker_at = text.rfind(TRUNCATION_MARKER);
+    if marker_at.is_some_and(|at| at <= max_bytes) && text.ends_with(" bytes]") {
+        return;
+    }
+
+    // Cut on a char boundary so the result stays valid UTF-8
+    let mut cut = max_bytes;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+
+    let removed = text.len() - cut;
+    text.truncate(cut);
+    text.push_str(&format!("{}{} bytes]", TRUNCATION_MARKER, removed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Subsystem;
+
+    #[test]
+    fn confidence_follows_the_result_subsystem() {
+        let checks = vec![
+            CheckResult::error("mute_state", "Output is muted", "Unmute")
+                .with_subsystem(Subsystem::Volume),
+            CheckResult::warning("sink_inputs", "'Firefox' is muted", "Unmute it")
+                .with_subsystem(Subsystem::Volume),
+            CheckResult::error("audio_devices", "No audio devices", "Check the driver")
+                .with_subsystem(Subsystem::Hardware),
+        ];
+
+        assert_eq!(cause_confidence(&checks), 0.6);
+    }
+
+    #[test]
+    fn debug_limit_can_be_applied_twice() {
+        let mut report = build_report(vec![
+            CheckResult::ok("mute_state", "Output is not muted").with_debug("x".repeat(100))
+        ]);
+        apply_debug_limit(&mut report, 10);
+        let once = report.checks[0].debug_info.clone();
+        apply_debug_limit(&mut report, 10);
+
+        assert_eq!(report.checks[0].debug_info, once);
+        assert_eq!(
+            once.as_deref(),
+            Some("xxxxxxxxxx\n... [truncated 90 bytes]")
+        );
+    }
+
+    #[test]
+    fn foundational_fixes_come_first() {
+        let report = build_report(vec![
+            CheckResult::error("sink_inputs", "'Firefox' is muted", "Unmute it"),
+            CheckResult::warning("mute_state", "Volume is very low (3%)", "Raise it"),
+            CheckResult::error("audio_stack", "No audio server detected", "Start PipeWire"),
+        ]);
+
+        assert_eq!(
+            report.suggested_fixes,
+            vec![
+                "No audio server detected: Start PipeWire",
+                "Volume is very low (3%): Raise it",
+                "'Firefox' is muted: Unmute it",
+            ]
+        );
     }
 }
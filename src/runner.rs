@@ -1,6 +1,38 @@
 //! Command execution utilities for running system commands safely.
 
-use std::process::Command;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long `run_command` waits before killing a hung child (e.g. a wedged session bus).
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default cap on captured output for commands that can produce huge output
+/// (pw-dump, journalctl, dmesg).
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 4 * 1024 * 1024;
+
+/// How many times `run_command_retry` re-runs a refused command.
+pub const STARTUP_RETRIES: u32 = 2;
+
+/// Delay before the first retry in `run_command_retry`; doubles after each.
+pub const STARTUP_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// How often to poll a running child for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// `-v` count; at `TRACE_VERBOSITY` or above every command run is logged.
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Verbosity (`-vv`) at which `run_command` and friends log each command to stderr.
+pub const TRACE_VERBOSITY: u8 = 2;
 
 /// Result of running a command.
 #[derive(Debug, Clone)]
@@ -8,21 +40,596 @@ pub struct CommandOutput {
     pub stdout: String,
     pub stderr: String,
     pub success: bool,
+    /// True if stdout or stderr was cut off at the capture limit.
+    pub truncated: bool,
+    /// True if stdout held bytes that aren't valid UTF-8. They show up as
+    /// U+FFFD in `stdout`, so names read from it may not compare equal to the
+    /// same name read from another command (see `sink_names_match`).
+    pub invalid_utf8: bool,
 }
 
-/// Run a command and capture its output.
-/// Never panics - returns a failed CommandOutput if the command cannot be executed.
-pub fn run_command(program: &str, args: &[&str]) -> CommandOutput {
-    match Command::new(program).args(args).output() {
-        Ok(output) => CommandOutput {
-            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-            success: output.status.success(),
-        },
-        Err(e) => CommandOutput {
+impl CommandOutput {
+    fn failed(e: std::io::Error) -> Self {
+        CommandOutput {
             stdout: String::new(),
             stderr: format!("Failed to execute command: {}", e),
             success: false,
-        },
+            truncated: false,
+            invalid_utf8: false,
+        }
+    }
+
+    /// A debug line saying `command`'s output held invalid UTF-8, or an
+    /// empty string if it didn't.
+    pub fn utf8_note(&self, command: &str) -> String {
+        if self.invalid_utf8 {
+            format!(
+                "note: {} output contained invalid UTF-8 (shown as U+FFFD); names are matched treating runs of replacement characters as equal\n",
+                command
+            )
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Something that can run a command and capture its output.
+///
+/// Checks that take a `&dyn CommandRunner` instead of calling `run_command`
+/// directly can be fed canned output.
+pub trait CommandRunner: Sync {
+    /// Run `program` with `args`, with the same guarantees as `run_command`.
+    fn run(&self, program: &str, args: &[&str]) -> CommandOutput;
+
+    /// Returns true if `program` is available to `run`.
+    fn is_installed(&self, program: &str) -> bool;
+
+    /// Returns true if the output comes from this machine, so local files
+    /// such as `/proc/asound` describe the same system.
+    fn is_live(&self) -> bool {
+        true
+    }
+}
+
+/// Runs commands on the live system like `run_command`.
+///
+/// Checks keep using the plain program name; a program with an override
+/// (`--pactl-path`) is spawned from its path instead.
+#[derive(Debug, Clone, Default)]
+pub struct SystemRunner {
+    program_paths: Vec<(String, PathBuf)>,
+}
+
+impl SystemRunner {
+    /// Run `path` whenever a command asks for `program`, for every
+    /// `(program, path)` pair in `paths`, e.g. `("pactl", "/opt/pulse/bin/pactl")`.
+    pub fn with_program_paths(paths: Vec<(String, PathBuf)>) -> Self {
+        SystemRunner {
+            program_paths: paths,
+        }
+    }
+
+    /// The executable to spawn for `program`: its override if one is set,
+    /// otherwise the name itself for a PATH lookup.
+    fn resolve<'a>(&'a self, program: &'a str) -> &'a OsStr {
+        self.program_paths
+            .iter()
+            .find(|(name, _)| name == program)
+            .map(|(_, path)| path.as_os_str())
+            .unwrap_or_else(|| OsStr::new(program))
+    }
+}
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str]) -> CommandOutput {
+        run_with_limits(self.resolve(program), args, DEFAULT_TIMEOUT, usize::MAX)
+    }
+
+    /// Whether `program` can be executed (found on PATH, or overridden).
+    fn is_installed(&self, program: &str) -> bool {
+        // Looked up without spawning it, so a program that hangs can't stall the run
+        is_executable_on_path(self.resolve(program))
+    }
+}
+
+/// Whether `program` names an executable file: the path itself when it has a
+/// directory part, else the first match in `PATH`, as `Command` would find it.
+fn is_executable_on_path(program: &OsStr) -> bool {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return is_executable(path);
+    }
+
+    env::var_os("PATH")
+        .map(|dirs| env::split_paths(&dirs).any(|dir| is_executable(&dir.join(program))))
+        .unwrap_or(false)
+}
+
+/// Whether `path` is a regular file with an execute bit set.
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Replays command output saved in a directory instead of running anything
+/// (`--from-dump`), so a report sent by someone else can be analyzed offline.
+///
+/// Each command's stdout is read from the file `dump_file_name` gives it,
+/// e.g. `list-sinks.txt` for `pactl list sinks`. A command with no file
+/// fails as if its program were not installed.
+#[derive(Debug, Clone)]
+pub struct FileRunner {
+    dir: PathBuf,
+}
+
+impl FileRunner {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileRunner { dir: dir.into() }
+    }
+}
+
+impl CommandRunner for FileRunner {
+    fn run(&self, program: &str, args: &[&str]) -> CommandOutput {
+        let path = self.dir.join(dump_file_name(program, args));
+        let started = Instant::now();
+
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                trace(program, args, &format!("read {}", path.display()), started);
+                CommandOutput {
+                    invalid_utf8: std::str::from_utf8(&bytes).is_err(),
+                    stdout: String::from_utf8_lossy(&bytes).to_string(),
+                    stderr: String::new(),
+                    success: true,
+                    truncated: false,
+                }
+            }
+            Err(e) => {
+                trace(program, args, &format!("no {}", path.display()), started);
+                CommandOutput::failed(e)
+            }
+        }
+    }
+
+    /// A dump has `pactl` if it holds `info.txt`, and any other program if
+    /// it holds a file for one of its commands (`wpctl-status.txt`).
+    fn is_installed(&self, program: &str) -> bool {
+        if program == "pactl" {
+            return self.dir.join(dump_file_name("pactl", &["info"])).is_file();
+        }
+
+        let prefix = format!("{}-", program);
+        std::fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .any(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+            })
+            .unwrap_or(false)
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+}
+
+/// The file a `FileRunner` reads for a command: the arguments joined with
+/// `-` for `pactl` (`list sinks` -> `list-sinks.txt`), and the program name
+/// followed by its arguments, without leading dashes, for anything else
+/// (`aplay -l` -> `aplay-l.txt`, `pw-top -b -n 1` -> `pw-top-b-n-1.txt`).
+pub fn dump_file_name(program: &str, args: &[&str]) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    if program != "pactl" {
+        parts.push(program);
+    }
+    parts.extend(args.iter().map(|arg| arg.trim_start_matches('-')));
+
+    format!("{}.txt", parts.join("-"))
+}
+
+/// Command-line tool available for talking to the audio server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioCli {
+    Pactl,
+    Wpctl,
+    None,
+}
+
+/// Probe `runner` for `pactl`, falling back to `wpctl`.
+pub fn audio_cli(runner: &dyn CommandRunner) -> AudioCli {
+    if runner.is_installed("pactl") {
+        AudioCli::Pactl
+    } else if runner.is_installed("wpctl") {
+        AudioCli::Wpctl
+    } else {
+        AudioCli::None
+    }
+}
+
+/// Set the verbosity level (the number of `-v` flags).
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+/// Run a command and capture its output.
+/// Never panics - returns a failed CommandOutput if the command cannot be executed.
+/// The child runs with `LC_ALL=C` so its output is untranslated English.
+/// The child is killed if it runs longer than `DEFAULT_TIMEOUT`.
+pub fn run_command(program: &str, args: &[&str]) -> CommandOutput {
+    run_command_timeout(program, args, DEFAULT_TIMEOUT)
+}
+
+/// Run a command through `runner`, retrying up to `STARTUP_RETRIES` times
+/// while it fails with "Connection refused".
+///
+/// Right after login the audio server may still be starting, so a refused
+/// connection is retried after `STARTUP_RETRY_DELAY`, doubling each time.
+/// Any other failure, including a missing binary, is returned immediately.
+pub fn run_command_retry(
+    runner: &dyn CommandRunner,
+    program: &str,
+    args: &[&str],
+) -> CommandOutput {
+    let mut output = runner.run(program, args);
+    let mut delay = STARTUP_RETRY_DELAY;
+
+    for _ in 0..STARTUP_RETRIES {
+        let missing = output.stderr.contains("not found") || output.stderr.contains("No such file");
+        if output.success || missing || !output.stderr.contains("Connection refused") {
+            break;
+        }
+        thread::sleep(delay);
+        delay *= 2;
+        output = runner.run(program, args);
+    }
+
+    output
+}
+
+/// Run a command, killing it if it has not exited within `timeout`.
+///
+/// A timed-out command returns `success: false` with a note in stderr. The
+/// killed child is always reaped.
+pub fn run_command_timeout(program: &str, args: &[&str], timeout: Duration) -> CommandOutput {
+    run_with_limits(OsStr::new(program), args, timeout, usize::MAX)
+}
+
+/// Run a command, capturing at most `max_bytes` of stdout and of stderr.
+///
+/// Output is streamed from the child's pipes so memory stays bounded. Once
+/// stdout hits the limit the child is killed; the partial output is returned
+/// with `truncated` set and `success` left true, since the command itself did
+/// not fail. Excess stderr is drained and discarded so the child never blocks.
+pub fn run_command_bounded(program: &str, args: &[&str], max_bytes: usize) -> CommandOutput {
+    run_with_limits(OsStr::new(program), args, DEFAULT_TIMEOUT, max_bytes)
+}
+
+/// Spawn `program` with `args`, killing it after `timeout` and capturing at
+/// most `max_bytes` of each stream.
+fn run_with_limits(
+    program: &OsStr,
+    args: &[&str],
+    timeout: Duration,
+    max_bytes: usize,
+) -> CommandOutput {
+    let started = Instant::now();
+
+    // Parsers match English labels like "Mute:", so never let the user's locale translate them
+    let mut child = match Command::new(program)
+        .args(args)
+        .env("LC_ALL", "C")
+        .env("LANG", "C")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            trace(
+                &program.to_string_lossy(),
+                args,
+                &format!("failed to start: {}", e),
+                started,
+            );
+            return CommandOutput::failed(e);
+        }
+    };
+
+    let stdout_full = Arc::new(AtomicBool::new(false));
+
+    let stdout_reader = child.stdout.take().map(|mut stdout| {
+        let stdout_full = Arc::clone(&stdout_full);
+        thread::spawn(move || {
+            let (bytes, truncated) = read_capped(&mut stdout, max_bytes, false);
+            stdout_full.store(truncated, Ordering::SeqCst);
+            (bytes, truncated)
+        })
+    });
+
+    let stderr_reader = child
+        .stderr
+        .take()
+        .map(|mut stderr| thread::spawn(move || read_capped(&mut stderr, max_bytes, true)));
+
+    let (status, timed_out) = wait_with_deadline(&mut child, timeout, &stdout_full);
+
+    let (stdout_bytes, stdout_truncated) = stdout_reader
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    let (stderr_bytes, stderr_truncated) = stderr_reader
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+
+    let mut stderr = String::from_utf8_lossy(&stderr_bytes).to_string();
+    if timed_out {
+        if !stderr.is_empty() && !stderr.ends_with('\n') {
+            stderr.push('\n');
+        }
+        stderr.push_str(&format!("Command timed out after {:?}", timeout));
+    }
+
+    let status_ok = status.map(|s| s.success()).unwrap_or(false);
+
+    let outcome = match status {
+        _ if timed_out => "timed out".to_string(),
+        Some(status) => status.to_string(),
+        None => "status unknown".to_string(),
+    };
+    trace(&program.to_string_lossy(), args, &outcome, started);
+
+    // A capture cut off mid-character is only incomplete, not invalid
+    let invalid_utf8 = match std::str::from_utf8(&stdout_bytes) {
+        Ok(_) => false,
+        Err(e) => !(stdout_truncated && e.error_len().is_none()),
+    };
+
+    CommandOutput {
+        stdout: String::from_utf8_lossy(&stdout_bytes).to_string(),
+        stderr,
+        success: !timed_out && (status_ok || stdout_truncated),
+        truncated: stdout_truncated || stderr_truncated,
+        invalid_utf8,
+    }
+}
+
+/// At `TRACE_VERBOSITY`, log a finished command to stderr, e.g.
+/// `[run] pactl list sinks: exit status: 0 (12 ms)`.
+fn trace(program: &str, args: &[&str], outcome: &str, started: Instant) {
+    if VERBOSITY.load(Ordering::Relaxed) < TRACE_VERBOSITY {
+        return;
+    }
+
+    // A single eprintln per command, so checks running in parallel never
+    // interleave within a line
+    eprintln!(
+        "[run] {} {}: {} ({} ms)",
+        program,
+        args.join(" "),
+        outcome,
+        started.elapsed().as_millis()
+    );
+}
+
+/// Wait for `child` to exit, killing it on timeout or once stdout is full.
+/// Returns the exit status (if any) and whether the timeout fired.
+fn wait_with_deadline(
+    child: &mut Child,
+    timeout: Duration,
+    stdout_full: &AtomicBool,
+) -> (Option<ExitStatus>, bool) {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return (Some(status), false),
+            Ok(None) => {
+                let timed_out = Instant::now() >= deadline;
+                if timed_out || stdout_full.load(Ordering::SeqCst) {
+                    // Kill and reap so no zombie is left behind
+                    let _ = child.kill();
+                    let status = child.wait().ok();
+                    return (status, timed_out);
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            Err(_) => return (None, false),
+        }
+    }
+}
+
+/// Read up to `max_bytes` from `reader`. Returns the bytes and whether the
+/// limit was hit. With `drain`, keeps reading (and discarding) past the limit.
+fn read_capped(reader: &mut impl Read, max_bytes: usize, drain: bool) -> (Vec<u8>, bool) {
+    let mut captured = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+
+        if !truncated {
+            let room = max_bytes - captured.len();
+            captured.extend_from_slice(&buf[..n.min(room)]);
+            if n > room {
+                truncated = true;
+                if !drain {
+                    break;
+                }
+            }
+        }
+    }
+
+    (captured, truncated)
+}
+
+/// Answers commands with canned output keyed by the full command line
+/// (`"pactl list sinks"`), so checks can be tested against fixtures. A
+/// command without an answer fails as if its program were not installed.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct MockRunner {
+    outputs: Vec<(String, CommandOutput)>,
+}
+
+#[cfg(test)]
+impl MockRunner {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `command` with `stdout` and success.
+    pub(crate) fn with(self, command: &str, stdout: &str) -> Self {
+        self.with_output(command, stdout, true)
+    }
+
+    /// Answer `command` with `stdout` and the given exit status.
+    pub(crate) fn with_output(mut self, command: &str, stdout: &str, success: bool) -> Self {
+        self.outputs.push((
+            command.to_string(),
+            CommandOutput {
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+                success,
+                truncated: false,
+                invalid_utf8: false,
+            },
+        ));
+        self
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for MockRunner {
+    fn run(&self, program: &str, args: &[&str]) -> CommandOutput {
+        let command = std::iter::once(program)
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        self.outputs
+            .iter()
+            .find(|(known, _)| *known == command)
+            .map(|(_, output)| output.clone())
+            .unwrap_or_else(|| {
+                CommandOutput::failed(std::io::Error::from(std::io::ErrorKind::NotFound))
+            })
+    }
+
+    fn is_installed(&self, program: &str) -> bool {
+        self.outputs
+            .iter()
+            .any(|(command, _)| command.split(' ').next() == Some(program))
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump_file_names_follow_the_command() {
+        assert_eq!(
+            dump_file_name("pactl", &["list", "sinks"]),
+            "list-sinks.txt"
+        );
+        assert_eq!(dump_file_name("aplay", &["-l"]), "aplay-l.txt");
+        assert_eq!(
+            dump_file_name("pw-top", &["-b", "-n", "1"]),
+            "pw-top-b-n-1.txt"
+        );
+    }
+
+    #[test]
+    fn mock_runner_answers_known_commands_only() {
+        let runner = MockRunner::new().with("pactl get-default-sink", "speakers\n");
+
+        assert_eq!(
+            runner.run("pactl", &["get-default-sink"]).stdout,
+            "speakers\n"
+        );
+        assert!(!runner.run("pactl", &["list", "sinks"]).success);
+        assert!(runner.is_installed("pactl"));
+        assert!(!runner.is_installed("wpctl"));
+        assert_eq!(audio_cli(&runner), AudioCli::Pactl);
+    }
+
+    #[test]
+    fn timeout_note_starts_on_its_own_line() {
+        let output = run_with_limits(
+            OsStr::new("sh"),
+            &["-c", "printf oops >&2; exec sleep 5"],
+            Duration::from_millis(300),
+            DEFAULT_MAX_OUTPUT_BYTES,
+        );
+
+        assert!(!output.success);
+        assert!(output.stderr.starts_with("oops\nCommand timed out after"));
+    }
+
+    #[test]
+    fn read_capped_stops_at_the_limit() {
+        let input = vec![b'a'; 10_000];
+
+        let (captured, truncated) = read_capped(&mut input.as_slice(), 100, false);
+        assert_eq!(captured.len(), 100);
+        assert!(truncated);
+
+        let (captured, truncated) = read_capped(&mut input.as_slice(), 100, true);
+        assert_eq!(captured.len(), 100);
+        assert!(truncated);
+
+        let (captured, truncated) = read_capped(&mut input.as_slice(), 10_000, false);
+        assert_eq!(captured.len(), 10_000);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn commands_run_under_the_c_locale() {
+        let output = run_command("sh", &["-c", "printf '%s %s' \"$LC_ALL\" \"$LANG\""]);
+
+        assert_eq!(output.stdout, "C C");
+    }
+
+    #[test]
+    fn invalid_utf8_output_is_flagged() {
+        let output = run_command("sh", &["-c", "printf 'Odd\\377DAC'"]);
+
+        assert!(output.invalid_utf8);
+        assert_eq!(output.stdout, "Odd\u{FFFD}DAC");
+        assert!(output.utf8_note("printf").contains("invalid UTF-8"));
+    }
+
+    #[test]
+    fn valid_utf8_output_is_not_flagged() {
+        let output = run_command("sh", &["-c", "printf 'Café DAC'"]);
+
+        assert!(!output.invalid_utf8);
+        assert_eq!(output.utf8_note("printf"), "");
+    }
+
+    #[test]
+    fn system_runner_finds_programs_without_running_them() {
+        let runner = SystemRunner::default();
+        assert!(runner.is_installed("sh"));
+        assert!(!runner.is_installed("why-no-sound-no-such-program"));
+
+        let runner =
+            SystemRunner::with_program_paths(vec![("pactl".to_string(), PathBuf::from("/bin/sh"))]);
+        assert!(runner.is_installed("pactl"));
+
+        let runner = SystemRunner::with_program_paths(vec![(
+            "pactl".to_string(),
+            PathBuf::from("/nonexistent/pactl"),
+        )]);
+        assert!(!runner.is_installed("pactl"));
     }
 }
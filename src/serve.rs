@@ -0,0 +1,94 @@
+//! `--serve` mode: answer each connection on a Unix socket with a JSON report.
+
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use why_no_sound::output;
+use why_no_sound::types::DiagnosticReport;
+
+use crate::signals::{self, SIGINT, SIGTERM};
+
+/// How long a report is handed out again before the checks are re-run, so
+/// a widget polling every second doesn't hammer pactl.
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// How often the accept loop checks for a shutdown signal.
+const SHUTDOWN_POLL: Duration = Duration::from_millis(100);
+
+/// A client that stops reading is dropped after this long.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Listen on the socket at `path` and write `collect`'s report as one line
+/// of JSON to each client, then close the connection.
+///
+/// A report younger than `CACHE_TTL` is served again instead of running the
+/// checks. SIGTERM and SIGINT are caught so the socket file is removed before
+/// returning.
+pub fn run(path: &Path, mut collect: impl FnMut() -> DiagnosticReport) -> io::Result<()> {
+    remove_stale_socket(path)?;
+    let listener = UnixListener::bind(path)?;
+    eprintln!("Serving reports on {} (SIGTERM to stop)", path.display());
+
+    signals::catch(&[SIGINT, SIGTERM]);
+
+    let served = serve(&listener, &mut collect);
+    let removed = fs::remove_file(path);
+    served.and(removed)
+}
+
+/// Accept connections until a shutdown signal arrives.
+fn serve(listener: &UnixListener, collect: &mut dyn FnMut() -> DiagnosticReport) -> io::Result<()> {
+    // Non-blocking so the loop notices a shutdown signal between connections
+    listener.set_nonblocking(true)?;
+    let mut cached: Option<(Instant, Vec<u8>)> = None;
+
+    while !signals::received() {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::Interrupted => {
+                thread::sleep(SHUTDOWN_POLL);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let fresh = cached
+            .as_ref()
+            .is_some_and(|(at, _)| at.elapsed() < CACHE_TTL);
+        if !fresh {
+            let mut json = Vec::new();
+            output::print_json(&mut json, &collect(), true)?;
+            cached = Some((Instant::now(), json));
+        }
+
+        // A client that hangs up early only loses its own reply
+        if let Some((_, json)) = &cached {
+            let _ = stream
+                .set_nonblocking(false)
+                .and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+                .and_then(|_| stream.write_all(json));
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove a socket file left behind by a server that didn't shut down
+/// cleanly. A socket something still listens on is left alone, so `bind`
+/// reports it as in use, and so is anything that isn't a socket.
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let is_socket = fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket());
+    if !is_socket {
+        return Ok(());
+    }
+
+    match UnixStream::connect(path) {
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,31 @@
+//! Catch SIGINT/SIGTERM so `--watch` and `--serve` can stop cleanly.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub const SIGINT: i32 = 2;
+pub const SIGTERM: i32 = 15;
+
+static RECEIVED: AtomicBool = AtomicBool::new(false);
+
+extern "C" {
+    fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+}
+
+extern "C" fn on_signal(_signum: i32) {
+    RECEIVED.store(true, Ordering::SeqCst);
+}
+
+/// Record `signals` instead of letting them kill the process; poll with `received`.
+pub fn catch(signals: &[i32]) {
+    for &signum in signals {
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+        unsafe {
+            signal(signum, on_signal);
+        }
+    }
+}
+
+/// Whether one of the caught signals has arrived.
+pub fn received() -> bool {
+    RECEIVED.load(Ordering::SeqCst)
+}
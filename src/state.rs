@@ -0,0 +1,38 @@
+//! Small pieces of state remembered between runs, under
+//! `$XDG_STATE_HOME/why-no-sound` (default `~/.local/state/why-no-sound`).
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::xdg;
+
+/// File holding the name of the last default sink that passed the checks.
+const LAST_SINK_FILE: &str = "last_sink";
+
+/// The directory state files live in, or `None` if neither
+/// `XDG_STATE_HOME` nor `HOME` is set.
+pub fn state_dir() -> Option<PathBuf> {
+    Some(xdg::state_home()?.join("why-no-sound"))
+}
+
+/// The last known good default sink, if one was recorded.
+pub fn load_last_sink() -> Option<String> {
+    let path = state_dir()?.join(LAST_SINK_FILE);
+    let name = fs::read_to_string(path).ok()?;
+    let name = name.trim();
+
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Record `sink` as the last known good default sink.
+pub fn save_last_sink(sink: &str) -> io::Result<()> {
+    let dir = state_dir().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "neither XDG_STATE_HOME nor HOME is set",
+        )
+    })?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(LAST_SINK_FILE), format!("{}\n", sink))
+}
@@ -3,7 +3,8 @@
 use serde::{Deserialize, Serialize};
 
 /// Status of a diagnostic check.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Variants are ordered by severity, so `max()` yields the worst status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum CheckStatus {
     Ok,
@@ -20,6 +21,59 @@ impl CheckStatus {
             CheckStatus::Error => "❌",
         }
     }
+
+    /// Returns the textual marker used instead of emoji in plain output.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "[OK]",
+            CheckStatus::Warning => "[WARN]",
+            CheckStatus::Error => "[FAIL]",
+        }
+    }
+
+    /// Returns the stable lowercase token used in machine-readable output.
+    pub fn token(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Error => "error",
+        }
+    }
+}
+
+/// Part of the audio stack a check looks at, for grouping and filtering results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Subsystem {
+    /// The sound server (PipeWire, PulseAudio, JACK) and its clients' connection to it.
+    Server,
+    /// Sound cards, kernel drivers, and firmware.
+    Hardware,
+    /// Which output or input device and card profile are in use.
+    Device,
+    /// Where application streams go, including apps that bypass the server.
+    Routing,
+    /// Mute switches and volume levels.
+    Volume,
+    /// Bluetooth audio devices and their profiles.
+    Bluetooth,
+    /// Permission to open the sound devices.
+    Access,
+}
+
+impl Subsystem {
+    /// Returns the stable lowercase token used in machine-readable output.
+    pub fn token(&self) -> &'static str {
+        match self {
+            Subsystem::Server => "server",
+            Subsystem::Hardware => "hardware",
+            Subsystem::Device => "device",
+            Subsystem::Routing => "routing",
+            Subsystem::Volume => "volume",
+            Subsystem::Bluetooth => "bluetooth",
+            Subsystem::Access => "access",
+        }
+    }
 }
 
 /// Result of a single diagnostic check.
@@ -27,6 +81,10 @@ impl CheckStatus {
 pub struct CheckResult {
     /// Name of the check (for identification).
     pub name: String,
+    /// Stable code for the specific condition detected (e.g. `mute.sink_muted`).
+    /// Unlike `message`, this never changes wording between releases.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
     /// Status of the check.
     pub status: CheckStatus,
     /// Human-readable message explaining the result.
@@ -34,9 +92,18 @@ pub struct CheckResult {
     /// Optional suggestion for fixing the issue.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggestion: Option<String>,
+    /// Command that fixes the issue, as program and arguments (e.g. for one-click fixes).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_command: Option<Vec<String>>,
     /// Debug information (raw command output).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub debug_info: Option<String>,
+    /// How long the check took to run, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Part of the audio stack the check belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subsystem: Option<Subsystem>,
 }
 
 impl CheckResult {
@@ -44,10 +111,14 @@ impl CheckResult {
     pub fn ok(name: &str, message: impl Into<String>) -> Self {
         Self {
             name: name.to_string(),
+            code: None,
             status: CheckStatus::Ok,
             message: message.into(),
             suggestion: None,
+            fix_command: None,
             debug_info: None,
+            duration_ms: None,
+            subsystem: None,
         }
     }
 
@@ -55,10 +126,14 @@ impl CheckResult {
     pub fn warning(name: &str, message: impl Into<String>, suggestion: impl Into<String>) -> Self {
         Self {
             name: name.to_string(),
+            code: None,
             status: CheckStatus::Warning,
             message: message.into(),
             suggestion: Some(suggestion.into()),
+            fix_command: None,
             debug_info: None,
+            duration_ms: None,
+            subsystem: None,
         }
     }
 
@@ -66,13 +141,35 @@ impl CheckResult {
     pub fn error(name: &str, message: impl Into<String>, suggestion: impl Into<String>) -> Self {
         Self {
             name: name.to_string(),
+            code: None,
             status: CheckStatus::Error,
             message: message.into(),
             suggestion: Some(suggestion.into()),
+            fix_command: None,
             debug_info: None,
+            duration_ms: None,
+            subsystem: None,
         }
     }
 
+    /// Tag this result with a stable condition code.
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code.to_string());
+        self
+    }
+
+    /// Attach a concrete command (program followed by arguments) that fixes the issue.
+    pub fn with_fix<S: AsRef<str>>(mut self, command: &[S]) -> Self {
+        self.fix_command = Some(command.iter().map(|s| s.as_ref().to_string()).collect());
+        self
+    }
+
+    /// Tag this result with the part of the audio stack it concerns.
+    pub fn with_subsystem(mut self, subsystem: Subsystem) -> Self {
+        self.subsystem = Some(subsystem);
+        self
+    }
+
     /// Add debug info to this result.
     pub fn with_debug(mut self, debug: impl Into<String>) -> Self {
         self.debug_info = Some(debug.into());
@@ -80,9 +177,32 @@ impl CheckResult {
     }
 }
 
+/// A registered check, as listed by `--list-checks`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckInfo {
+    /// Name accepted by `--only` and `--skip`.
+    pub name: String,
+    /// One-line description of what the check looks for.
+    pub description: String,
+    /// Part of the audio stack the check looks at.
+    pub subsystem: Subsystem,
+    /// Whether the check runs with the current `--only`/`--skip` selection.
+    pub enabled: bool,
+}
+
+/// Version of the JSON report format. Bump whenever the report shape changes.
+pub const SCHEMA_VERSION: u32 = 5;
+
 /// The final diagnostic report.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiagnosticReport {
+    /// Worst status across all checks (`worst_status()` when the report was
+    /// built), so consumers needn't scan `checks`.
+    pub overall_status: CheckStatus,
+    /// Report format version (`SCHEMA_VERSION`), for defensive parsing.
+    pub schema_version: u32,
+    /// Version of why-no-sound that produced the report.
+    pub tool_version: String,
     /// All check results in order.
     pub checks: Vec<CheckResult>,
     /// Summary of the diagnosis.
@@ -90,6 +210,27 @@ pub struct DiagnosticReport {
     /// Probable root cause (if any issues found).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub probable_cause: Option<String>,
+    /// How likely the probable cause is the real one (0.0–1.0; 0.0 when there is none).
+    pub confidence: f32,
     /// Ordered list of suggested fixes.
     pub suggested_fixes: Vec<String>,
+    /// Full PipeWire graph from `pw-dump` (only with `--pw-dump`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pw_dump: Option<String>,
+}
+
+impl DiagnosticReport {
+    /// Returns the most severe status across all checks (Ok if there are none).
+    pub fn worst_status(&self) -> CheckStatus {
+        worst_status(&self.checks)
+    }
+}
+
+/// Returns the most severe status among `checks` (Ok if there are none).
+pub fn worst_status(checks: &[CheckResult]) -> CheckStatus {
+    checks
+        .iter()
+        .map(|c| c.status)
+        .max()
+        .unwrap_or(CheckStatus::Ok)
 }
@@ -0,0 +1,45 @@
+//! `--watch` mode: re-run the diagnosis on an interval until Ctrl-C.
+
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::signals::{self, SIGINT};
+
+/// How often the sleep between refreshes checks for Ctrl-C.
+const INTERRUPT_POLL: Duration = Duration::from_millis(100);
+
+/// Clear the screen and call `render` every `interval` until SIGINT.
+///
+/// Ctrl-C is caught so the current refresh finishes and the loop exits
+/// normally instead of killing the process mid-output.
+pub fn run(interval: Duration, mut render: impl FnMut()) {
+    signals::catch(&[SIGINT]);
+
+    while !signals::received() {
+        // Clear the screen and move the cursor home
+        print!("\x1B[2J\x1B[H");
+        println!(
+            "Every {}s, updated {} UTC (Ctrl-C to stop)",
+            interval.as_secs(),
+            utc_time_of_day()
+        );
+        render();
+
+        let deadline = Instant::now() + interval;
+        while Instant::now() < deadline && !signals::received() {
+            thread::sleep(INTERRUPT_POLL);
+        }
+    }
+
+    println!();
+}
+
+/// Current UTC time as `HH:MM:SS`.
+fn utc_time_of_day() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
@@ -0,0 +1,30 @@
+//! XDG base directories, with the `$HOME` fallbacks the specification gives
+//! when a variable is unset or empty.
+
+use std::path::PathBuf;
+
+/// `$XDG_CONFIG_HOME`, else `~/.config`.
+pub fn config_home() -> Option<PathBuf> {
+    base_dir("XDG_CONFIG_HOME", &[".config"])
+}
+
+/// `$XDG_STATE_HOME`, else `~/.local/state`.
+pub fn state_home() -> Option<PathBuf> {
+    base_dir("XDG_STATE_HOME", &[".local", "state"])
+}
+
+/// The directory in `var`, else `fallback` under `$HOME`; `None` if neither is set.
+fn base_dir(var: &str, fallback: &[&str]) -> Option<PathBuf> {
+    std::env::var(var)
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            let home = std::env::var("HOME").ok().filter(|h| !h.is_empty())?;
+            Some(
+                fallback
+                    .iter()
+                    .fold(PathBuf::from(home), |dir, part| dir.join(part)),
+            )
+        })
+}